@@ -1,8 +1,10 @@
 use std::convert::Infallible;
+use std::fs;
+use std::path::PathBuf;
 use clap::{Args, Parser, Subcommand};
 use clio::ClioPath;
 use ironworks_cli::Role;
-use ironworks_cli::Id;
+use ironworks_cli::{Gender, Id, Language, OutputFormat, PlayerContext, SeStringFormat, Sheet};
 
 /// A command line utility that can extract data from FFXIV's internal Excel sheets.
 #[derive(Parser, Debug)]
@@ -23,6 +25,72 @@ pub(crate) struct Cli {
     /// in which case this flag is required to manually update the header data.
     #[clap(global = true, long, short)]
     pub refresh: bool,
+    /// How many levels of references to other sheets are resolved.
+    ///
+    /// Columns that reference rows in other sheets are replaced by the referenced
+    /// row instead of its ID, e.g. with a depth of 1 an action's class job
+    /// is replaced by the corresponding row of the ClassJob sheet.
+    #[clap(global = true, long, default_value_t = 0)]
+    pub depth: u8,
+    /// The language that strings are printed in. One of `en`, `ja`, `de`, `fr`, `chs` or `ko`.
+    ///
+    /// Defaults to English. Note that the Chinese and Korean languages are
+    /// only available in the respective regional clients.
+    #[clap(global = true, long, short, value_parser = parse_language)]
+    pub language: Option<Language>,
+    /// Prints every string in all languages available for its sheet, as an object
+    /// keyed by language code (e.g. `{ "en": ..., "ja": ... }`).
+    #[clap(global = true, long, conflicts_with = "language")]
+    pub all_languages: bool,
+    /// Sorts the keys of all printed rows alphabetically, instead of
+    /// ordering them like the columns of the sheet.
+    #[clap(global = true, long)]
+    pub sort_keys: bool,
+    /// The format that results are printed in.
+    ///
+    /// Tabular formats (`csv` and `tsv`) start with a header row of column names.
+    /// Nested and linked columns are flattened, e.g. `ClassJob.Name` or `BaseParam[0]`.
+    #[clap(global = true, long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+    /// The format that strings of printed rows are written in.
+    ///
+    /// `tokens` writes each string as an array of text runs and macros (e.g. colors, line breaks
+    /// or conditionals) along with their arguments, e.g. to render tooltips faithfully.
+    /// `html` writes each string as sanitized HTML, with colors and emphasis as styled spans.
+    /// `plain` writes each string as normalized text without soft hyphens, icons or
+    /// special spaces. `search` matches the same text, but with every branch of conditional text.
+    /// `markdown` writes each string as escaped Markdown with emphasis; this is the default for `--format markdown`.
+    #[clap(global = true, long, value_enum, default_value_t = SeStringFormat::Text)]
+    pub sestring: SeStringFormat,
+    /// Evaluates conditional text in strings for a specific character, e.g. `level=90,job=PLD,gender=female`.
+    ///
+    /// Strings then contain the text that the character would see in game, e.g. the additional effects
+    /// of an action that it has learned. Any of `level`, `job` (an ID or abbreviation) and `gender` can be omitted.
+    #[clap(global = true, long, value_parser = parse_context)]
+    pub context: Option<PlayerContext>,
+    /// Includes a description of every printed column (its kind, offset and referenced sheets)
+    /// in the output of commands that print rows, e.g. to generate types from.
+    ///
+    /// The output then becomes an object with the keys `schema` and `data`.
+    /// This has no effect on tabular formats.
+    #[clap(global = true, long)]
+    pub schema: bool,
+    /// Writes the output to the given file instead of stdout.
+    ///
+    /// If the path is a directory (or ends with a slash), commands that print multiple rows,
+    /// classes and jobs, search matches or icons write one file per entity instead,
+    /// e.g. `<id>.json` or `<id>.png`. Files only appear once they have been written completely.
+    /// `database` and `parquet` write to their own path argument instead.
+    #[clap(global = true, long, short, value_parser)]
+    pub out: Option<ClioPath>,
+    /// Renders the output through the given template file instead of using `--format`,
+    /// e.g. to generate wiki markup. Uses Jinja2-like syntax, where the result
+    /// is available as `data` in the same shape as its JSON output.
+    ///
+    /// Additional filters: `icon_path`, `icon_file`, `newlines(separator)`, `linked(key)`,
+    /// and `html`, `markdown` and `plain`, which render a string like the respective `--sestring` format.
+    #[clap(global = true, long)]
+    pub template: Option<PathBuf>,
     /// Prints the version of the application and the game directory (if specified or found).
     #[clap(global = true, long, short, num_args = 0..2, require_equals = true, default_missing_value = "", default_value = "Option::None")]
     pub version: Option<String>
@@ -37,9 +105,30 @@ pub(crate) enum Command {
     Action(SheetCommandArgs),
     /// Retrieves JSON information about a specific status effect.
     Status(SheetCommandArgs),
+    /// Retrieves JSON information about a specific item.
+    Item(SheetCommandArgs),
+    /// Retrieves JSON information about a row in any Excel sheet.
+    #[clap(name = "sheet")]
+    Sheet(GenericSheetCommandArgs),
+    /// Prints a JSON array of every row in a sheet.
+    /// The same columns as in single-row commands are included.
+    #[clap(name = "export")]
+    Export(ExportArgs),
+    /// Writes every row of one or more sheets into a SQLite database,
+    /// with one table per sheet.
+    #[clap(name = "database")]
+    Database(DatabaseArgs),
+    /// Writes every row of a sheet into a Parquet file,
+    /// e.g. to load it with pandas or DuckDB.
+    #[clap(name = "parquet")]
+    Parquet(ParquetArgs),
     /// Prints an array of the numerical IDs of all job actions for a specific class or job.
     #[clap(name = "job-actions")]
     JobActions(JobActionsCommandArgs),
+    /// Prints a JSON array of all classes and jobs,
+    /// or information about a specific class or job.
+    #[clap(name = "jobs")]
+    Jobs(JobsArgs),
     /// Prints an array of the numerical IDs of all role actions for a specific role.
     #[clap(name = "role-actions")]
     RoleActions(RoleActionsCommandArgs),
@@ -52,6 +141,12 @@ pub(crate) enum Command {
 pub(crate) struct SheetCommandArgs {
     /// The ID of the item that information should be retrieved about.
     /// Can also be a string to search for an item by name.
+    ///
+    /// Multiple rows can be retrieved at once by passing a range (`100-200`),
+    /// a comma-separated list (`7,8,9`) or a file containing IDs (`@ids.txt`).
+    ///
+    /// For subrow sheets, all subrows of the row are retrieved unless a single
+    /// subrow is specified as `row:subrow` (e.g. `100:2`).
     #[clap(value_parser = parse_id)]
     pub id: Id,
     /// Whether to pretty-print the result.
@@ -59,6 +154,46 @@ pub(crate) struct SheetCommandArgs {
     pub pretty: bool
 }
 
+#[derive(Args, Debug)]
+pub(crate) struct GenericSheetCommandArgs {
+    /// The name of the sheet, e.g. "Item" or "ClassJob".
+    /// Must be known to both the game and EXDSchema.
+    #[clap(value_parser = parse_sheet)]
+    pub sheet: Sheet,
+    #[clap(flatten)]
+    pub base: SheetCommandArgs
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ExportArgs {
+    /// The name of the sheet to export, e.g. "Action" or "Item".
+    #[clap(value_parser = parse_sheet)]
+    pub sheet: Sheet,
+    /// Whether to pretty-print the result.
+    #[clap(short, long)]
+    pub pretty: bool
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct DatabaseArgs {
+    /// The path of the SQLite database. It is created if it doesn't exist yet;
+    /// otherwise the tables of the written sheets are replaced.
+    pub path: PathBuf,
+    /// The names of the sheets to write, e.g. "Action" or "Item".
+    /// If not specified, the Action, Status, ContentFinderCondition and Item sheets are written.
+    #[clap(value_parser = parse_sheet)]
+    pub sheets: Vec<Sheet>
+}
+
+#[derive(Args, Debug)]
+pub(crate) struct ParquetArgs {
+    /// The name of the sheet to write, e.g. "Action" or "Item".
+    #[clap(value_parser = parse_sheet)]
+    pub sheet: Sheet,
+    /// The path of the Parquet file. An existing file is replaced.
+    pub path: PathBuf
+}
+
 #[derive(Args, Debug)]
 pub(crate) struct JobActionsCommandArgs {
     #[clap(flatten)]
@@ -68,6 +203,17 @@ pub(crate) struct JobActionsCommandArgs {
     pub names: bool
 }
 
+#[derive(Args, Debug)]
+pub(crate) struct JobsArgs {
+    /// The ID or abbreviation (e.g. "PLD") of the class or job to retrieve.
+    /// If not specified, all classes and jobs are printed.
+    #[clap(value_parser = parse_id)]
+    pub id: Option<Id>,
+    /// Whether to pretty-print the result.
+    #[clap(short, long)]
+    pub pretty: bool
+}
+
 #[derive(Args, Debug)]
 pub(crate) struct RoleActionsCommandArgs {
     #[clap(value_enum)]
@@ -82,10 +228,171 @@ pub(crate) struct RoleActionsCommandArgs {
 
 #[derive(Args, Debug)]
 pub(crate) struct IconArgs {
-    /// The ID of the item that information should be retrieved about.
-    pub id: u32
+    /// The ID of the icon to retrieve.
+    ///
+    /// Multiple icons can be retrieved at once by passing a range (`100-200`),
+    /// a comma-separated list (`7,8,9`) or a file containing IDs (`@ids.txt`),
+    /// in which case `--out` must be a directory.
+    #[clap(value_parser = parse_id)]
+    pub id: Id
+}
+
+fn parse_id(input: &str) -> Result<Id, String> {
+    if let Some(path) = input.strip_prefix('@') {
+        let contents = fs::read_to_string(path).map_err(|e| format!("failed to read \"{}\": {}", path, e))?;
+
+        return parse_id_list(contents.split([',', '\n', '\r', ' ', '\t']).filter(|x| !x.is_empty()))?
+            .ok_or_else(|| format!("\"{}\" does not only contain IDs or ID ranges", path));
+    }
+
+    if let Ok(index) = input.parse::<u32>() {
+        return Ok(Id::Index(index));
+    }
+
+    if let Some((row, subrow)) = input.split_once(':') {
+        if let (Ok(row), Ok(subrow)) = (row.parse::<u32>(), subrow.parse::<u16>()) {
+            return Ok(Id::Subrow(row, subrow));
+        }
+    }
+
+    // Anything that isn't entirely made up of IDs and ranges is a name to search for.
+    Ok(parse_id_list(input.split(','))?.unwrap_or_else(|| Id::Name(input.to_owned())))
 }
 
-fn parse_id(input: &str) -> Result<Id, Infallible> {
-    Ok(input.parse::<u32>().map_or(Id::Name(input.to_owned()), Id::Index))
+/// The maximum number of IDs that a list of IDs and ID ranges may contain.
+const MAX_ID_COUNT: u64 = 1_000_000;
+
+/// Parses a list of IDs and ID ranges (e.g. `100-200`) into [`Id::Indices`].
+/// Returns [`None`] if any of the elements isn't an ID or range, or an error if a range
+/// ends before it starts or the list contains more than [`MAX_ID_COUNT`] IDs.
+fn parse_id_list<'a>(elements: impl Iterator<Item = &'a str>) -> Result<Option<Id>, String> {
+    let mut ranges = Vec::new();
+
+    for element in elements {
+        let element = element.trim();
+
+        let range = if let Some((start, end)) = element.split_once('-') {
+            let (Ok(start), Ok(end)) = (start.trim().parse::<u32>(), end.trim().parse::<u32>()) else {
+                return Ok(None);
+            };
+
+            start..=end
+        } else {
+            let Ok(id) = element.parse::<u32>() else {
+                return Ok(None);
+            };
+
+            id..=id
+        };
+
+        ranges.push(range);
+    }
+
+    if let Some(range) = ranges.iter().find(|x| x.start() > x.end()) {
+        return Err(format!("invalid range \"{}-{}\", its start must not be greater than its end", range.start(), range.end()));
+    }
+
+    // Ranges are only expanded once the list is known to be of a sane size.
+    let count: u64 = ranges.iter().map(|x| (*x.end() - *x.start()) as u64 + 1).sum();
+
+    if count > MAX_ID_COUNT {
+        return Err(format!("too many IDs ({}), at most {} can be requested at once", count, MAX_ID_COUNT));
+    }
+
+    Ok(Some(Id::Indices(ranges.into_iter().flatten().collect())))
+}
+
+fn parse_sheet(input: &str) -> Result<Sheet, Infallible> {
+    Ok(input.parse::<Sheet>().unwrap_or_else(|_| Sheet::Other(input.to_owned())))
+}
+
+fn parse_context(input: &str) -> Result<PlayerContext, String> {
+    let mut context = PlayerContext::default();
+
+    for element in input.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+        let (key, value) = element.split_once('=').ok_or_else(|| format!("\"{}\" is not of the form key=value", element))?;
+        let value = value.trim();
+
+        match key.trim().to_lowercase().as_str() {
+            "level" => context.level = Some(value.parse::<u32>().map_err(|_| format!("invalid level \"{}\"", value))?),
+            "job" => context.job = Some(value.parse::<u32>().map(Id::Index).unwrap_or_else(|_| Id::Name(value.to_owned()))),
+            "gender" => context.gender = Some(match value.to_lowercase().as_str() {
+                "male" | "m" => Gender::Male,
+                "female" | "f" => Gender::Female,
+                _ => return Err(format!("unknown gender \"{}\"", value))
+            }),
+            key => return Err(format!("unknown context key \"{}\"", key))
+        }
+    }
+
+    Ok(context)
+}
+
+fn parse_language(input: &str) -> Result<Language, String> {
+    match input.to_lowercase().as_str() {
+        "en" | "english" => Ok(Language::English),
+        "ja" | "japanese" => Ok(Language::Japanese),
+        "de" | "german" => Ok(Language::German),
+        "fr" | "french" => Ok(Language::French),
+        "chs" | "chinese" => Ok(Language::ChineseSimplified),
+        "ko" | "korean" => Ok(Language::Korean),
+        _ => Err(format!("unknown language \"{}\"", input))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn indices(input: &str) -> Vec<u32> {
+        match parse_id(input) {
+            Ok(Id::Indices(indices)) => indices,
+            result => panic!("expected indices for \"{}\", got {:?}", input, result)
+        }
+    }
+
+    #[test]
+    fn parses_single_ids_and_subrows() {
+        assert!(matches!(parse_id("7"), Ok(Id::Index(7))));
+        assert!(matches!(parse_id("7:2"), Ok(Id::Subrow(7, 2))));
+    }
+
+    #[test]
+    fn parses_ranges_and_lists() {
+        assert_eq!(indices("1-3"), [1, 2, 3]);
+        assert_eq!(indices("5,1-2, 9"), [5, 1, 2, 9]);
+        assert_eq!(indices(" 4 - 5 ,6"), [4, 5, 6]);
+    }
+
+    #[test]
+    fn parses_names_with_dashes_and_commas() {
+        for name in ["Sastasha", "Hells' Lid", "Mhach, Ruins", "Alpha-Omega", "2-Handed", "1-2,Three"] {
+            assert!(matches!(parse_id(name), Ok(Id::Name(x)) if x == name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn rejects_reversed_ranges() {
+        assert!(parse_id("200-100").is_err());
+        assert!(parse_id("1,200-100").is_err());
+    }
+
+    #[test]
+    fn parses_id_files() {
+        let path = std::env::temp_dir().join(format!("ironworks_cli_ids_{}.txt", std::process::id()));
+        fs::write(&path, "1\n3-4, 6\r\n\t8 ").unwrap();
+
+        let result = parse_id(&format!("@{}", path.display()));
+        fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Ok(Id::Indices(x)) if x == [1, 3, 4, 6, 8]));
+        assert!(parse_id(&format!("@{}", path.display())).is_err());
+    }
+
+    #[test]
+    fn limits_id_count() {
+        assert_eq!(indices(&format!("1-{}", MAX_ID_COUNT)).len() as u64, MAX_ID_COUNT);
+        assert!(parse_id(&format!("0-{}", MAX_ID_COUNT)).is_err());
+        assert!(parse_id(&format!("1-{},0", MAX_ID_COUNT)).is_err());
+    }
 }
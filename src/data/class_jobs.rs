@@ -0,0 +1,200 @@
+use std::ops::Deref;
+use serde::Serialize;
+use strum::IntoStaticStr;
+use crate::{data::sheet_extractor::{get_u32, SheetColumn}, err::{Err, ToUnknownErr}};
+use super::{value_cell, write_json, write_json_lines, Id, IronworksCli, Table, TabularResult, Value, WritableResult};
+
+const SHEET_NAME: &str = "ClassJob";
+
+/// The discipline that a class or job belongs to, as determined by its `ClassJobCategory`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, IntoStaticStr, Serialize)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum Discipline {
+    /// Disciples of War and Magic.
+    Combat,
+    /// Disciples of the Hand.
+    Crafting,
+    /// Disciples of the Land.
+    Gathering
+}
+
+impl Discipline {
+    fn from_category(category: u32) -> Option<Self> {
+        match category {
+            30 | 31 => Some(Discipline::Combat),
+            32 => Some(Discipline::Gathering),
+            33 => Some(Discipline::Crafting),
+            _ => None
+        }
+    }
+}
+
+/// Represents a class or job, as returned by [`IronworksCli::get_jobs()`] or [`IronworksCli::get_job()`].
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClassJob {
+    pub id: u32,
+    pub name: Value,
+    pub abbreviation: String,
+    /// The ID of the base class. For classes, this is the class's own ID.
+    pub parent: u32,
+    /// The role of the class or job, e.g. `1` for tanks.
+    pub role: u32,
+    /// The index of the job. This is `0` for classes and non-combat jobs.
+    pub job_index: u32,
+    /// The discipline of the class or job. [`None`] for the base adventurer class.
+    pub discipline: Option<Discipline>
+}
+
+/// Represents a list of classes and jobs returned by the [`IronworksCli::get_jobs()`] function.
+#[derive(Debug, Serialize)]
+pub struct ClassJobs(Vec<ClassJob>);
+
+impl Deref for ClassJobs {
+    type Target = [ClassJob];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl IronworksCli {
+    /// Gets all classes and jobs.
+    pub fn get_jobs(&self) -> Result<ClassJobs, Err> {
+        let mut jobs: Vec<ClassJob> = Vec::new();
+        let sheet_info = self.get_sheet(SHEET_NAME)?;
+
+        let columns: Vec<SheetColumn> = sheet_info.filtered_columns(&["Name", "Abbreviation", "ClassJobParent", "Role", "JobIndex", "ClassJobCategory"])?.collect();
+        let column = |name: &str, error_code: u32| columns.iter().find(|x| x.name == name).and_then(SheetColumn::column).to_unknown_err(error_code);
+        let name_column = column("Name", 34)?;
+        let abbreviation_column = column("Abbreviation", 35)?;
+        let parent_column = column("ClassJobParent", 36)?;
+        let role_column = column("Role", 37)?;
+        let job_index_column = column("JobIndex", 38)?;
+        let category_column = column("ClassJobCategory", 39)?;
+
+        for row in sheet_info.sheet.into_iter() {
+            let name = row.field(name_column).to_unknown_err(40)?;
+
+            // Unused rows have no name.
+            if name.as_string().to_unknown_err(41)?.to_string().is_empty() {
+                continue;
+            }
+
+            let number = |column| get_u32(&row.field(column).to_unknown_err(42)?).to_unknown_err(43);
+
+            jobs.push(ClassJob {
+                id: row.row_id(),
+                name: self.field_value(name)?,
+                abbreviation: row.field(abbreviation_column).to_unknown_err(44)?.as_string().to_unknown_err(45)?.to_string(),
+                parent: number(parent_column)?,
+                role: number(role_column)?,
+                job_index: number(job_index_column)?,
+                discipline: Discipline::from_category(number(category_column)?)
+            });
+        }
+
+        Ok(ClassJobs(jobs))
+    }
+
+    /// Gets a single class or job by its ID or abbreviation.
+    /// Abbreviations are matched case-insensitively.
+    pub fn get_job(&self, id: Id) -> Result<ClassJob, Err> {
+        let jobs = self.get_jobs()?.0;
+
+        match id {
+            Id::Index(id) => jobs.into_iter().find(|x| x.id == id).ok_or(Err::JobNotFound(id)),
+            Id::Name(abbreviation) => jobs.into_iter()
+                .find(|x| x.abbreviation.eq_ignore_ascii_case(&abbreviation))
+                .ok_or(Err::JobAcronymNotFound(abbreviation)),
+            Id::Indices(_) | Id::Subrow(_, _) => Err(Err::UnsupportedId)
+        }
+    }
+}
+
+impl WritableResult for ClassJob {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, false)
+    }
+
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+}
+
+impl WritableResult for ClassJobs {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(&self.0, w, false)
+    }
+
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(&self.0, w, true)
+    }
+
+    fn write_lines(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json_lines(self.0.iter(), w)
+    }
+}
+
+impl ClassJob {
+    const HEADER: [&'static str; 7] = ["Id", "Name", "Abbreviation", "Parent", "Role", "JobIndex", "Discipline"];
+
+    fn record(&self) -> Vec<String> {
+        vec![
+            self.id.to_string(),
+            value_cell(&self.name),
+            self.abbreviation.clone(),
+            self.parent.to_string(),
+            self.role.to_string(),
+            self.job_index.to_string(),
+            self.discipline.map_or("", <&'static str>::from).to_owned()
+        ]
+    }
+}
+
+impl TabularResult for ClassJob {
+    fn table(&self) -> Table {
+        let mut table = Table::new(ClassJob::HEADER.map(String::from).to_vec());
+        table.push(self.record());
+
+        table
+    }
+}
+
+impl TabularResult for ClassJobs {
+    fn table(&self) -> Table {
+        let mut table = Table::new(ClassJob::HEADER.map(String::from).to_vec());
+        self.0.iter().for_each(|x| table.push(x.record()));
+
+        table
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class_job_round_trips() {
+        let job = ClassJob {
+            id: 19,
+            name: Value::Formatted("pa\"lad\\in\n".to_owned()),
+            abbreviation: "P\u{7f}LD".to_owned(),
+            parent: 1,
+            role: 1,
+            job_index: 1,
+            discipline: Some(Discipline::Combat)
+        };
+
+        let mut output = Vec::new();
+        job.pretty_write(&mut output).unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(parsed["name"], "pa\"lad\\in\n");
+        assert_eq!(parsed["abbreviation"], job.abbreviation);
+        assert_eq!(parsed["jobIndex"], 1);
+        assert_eq!(parsed["discipline"], "combat");
+    }
+}
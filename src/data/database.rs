@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+use std::path::Path;
+use ironworks::excel::Field;
+use ironworks::file::exh::ColumnKind;
+use rusqlite::types::Value as SqlValue;
+use rusqlite::{params_from_iter, Connection, Transaction};
+use crate::err::Err;
+use super::format::flatten_fields;
+use super::sheet_extractor::FlatColumn;
+use super::sheets::Sheet;
+use super::IronworksCli;
+
+/// The sheets that are written to a database if no sheets are specified.
+pub const DEFAULT_DATABASE_SHEETS: [Sheet; 4] = [Sheet::Action, Sheet::Status, Sheet::ContentFinderCondition, Sheet::Item];
+
+impl IronworksCli {
+    /// Writes every row of the given sheets into the SQLite database at the given path,
+    /// creating it if necessary. Each sheet is written to a table of the same name,
+    /// replacing any existing table.
+    ///
+    /// The same columns and links as in [`IronworksCli::get()`] are written,
+    /// with nested columns flattened (e.g. `BaseParam[0]`). Fields are always written
+    /// as they are stored in the sheet; instead of resolving references, a foreign key
+    /// is declared for every column that references one of the other written sheets.
+    ///
+    /// Note that SQLite doesn't enforce foreign keys by default. Many sheets use
+    /// row `0` to signify an empty reference, even if the referenced sheet has no such row.
+    pub fn write_database(&self, sheets: &[Sheet], path: &Path) -> Result<(), Err> {
+        let mut connection = Connection::open(path).map_err(Err::DatabaseError)?;
+        let transaction = connection.transaction().map_err(Err::DatabaseError)?;
+        let sheet_names: Vec<&str> = sheets.iter().map(Sheet::name).collect();
+
+        for sheet in sheets {
+            self.write_table(&transaction, sheet.name(), &sheet_names)?;
+        }
+
+        transaction.commit().map_err(Err::DatabaseError)
+    }
+
+    fn write_table(&self, transaction: &Transaction, sheet_name: &str, sheet_names: &[&str]) -> Result<(), Err> {
+        let sheet_info = self.get_sheet(sheet_name)?;
+        let extractor = self.raw_row_extractor(&sheet_info)?;
+        let has_subrows = sheet_info.has_subrows()?;
+        let columns = extractor.flat_columns();
+        let column_indices: HashMap<&str, usize> = columns.iter().enumerate().map(|(i, x)| (x.name.as_str(), i)).collect();
+
+        let mut definitions = vec!["\"id\" INTEGER NOT NULL".to_owned()];
+        let mut constraints = vec![];
+
+        if has_subrows {
+            definitions.push("\"subrow_id\" INTEGER NOT NULL".to_owned());
+            constraints.push("PRIMARY KEY (\"id\", \"subrow_id\")".to_owned());
+        } else {
+            constraints.push("PRIMARY KEY (\"id\")".to_owned());
+        }
+
+        for column in columns.iter() {
+            definitions.push(format!("{} {}", quote(&column.name), sql_type(column.column.kind())));
+
+            if let Some(target) = foreign_key_target(column, sheet_names) {
+                constraints.push(format!("FOREIGN KEY ({}) REFERENCES {} (\"id\")", quote(&column.name), quote(target)));
+            }
+        }
+
+        // Linked sheets share their row IDs with this sheet.
+        for linked_sheet in extractor.linked_sheets().filter(|x| sheet_names.contains(x)) {
+            constraints.push(format!("FOREIGN KEY (\"id\") REFERENCES {} (\"id\")", quote(linked_sheet)));
+        }
+
+        let table = quote(sheet_name);
+        definitions.append(&mut constraints);
+
+        transaction.execute(&format!("DROP TABLE IF EXISTS {}", table), []).map_err(Err::DatabaseError)?;
+        transaction.execute(&format!("CREATE TABLE {} ({})", table, definitions.join(", ")), []).map_err(Err::DatabaseError)?;
+
+        let placeholders = vec!["?"; columns.len() + if has_subrows { 2 } else { 1 }].join(", ");
+        let mut statement = transaction.prepare(&format!("INSERT INTO {} VALUES ({})", table, placeholders)).map_err(Err::DatabaseError)?;
+
+        for row in sheet_info.sheet.into_iter() {
+            let values = extractor.extract(&row)?;
+            let mut params = vec![SqlValue::Integer(row.row_id().into())];
+
+            if has_subrows {
+                params.push(SqlValue::Integer(row.subrow_id().into()));
+            }
+
+            let offset = params.len();
+            params.resize(offset + columns.len(), SqlValue::Null);
+
+            flatten_fields(&values, "", &mut |name, field| {
+                if let Some(i) = column_indices.get(name.as_str()) {
+                    params[offset + i] = sql_value(field);
+                }
+            });
+
+            statement.execute(params_from_iter(params)).map_err(Err::DatabaseError)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Gets the sheet that a column references if it can be declared as a foreign key,
+/// i.e. if it unconditionally references the row ID of one of the written sheets.
+fn foreign_key_target<'a>(column: &'a FlatColumn, sheet_names: &[&str]) -> Option<&'a str> {
+    match column.references {
+        [target] if target.selector.is_none() && target.condition.is_none() && sheet_names.contains(&target.sheet.as_str()) => Some(&target.sheet),
+        _ => None
+    }
+}
+
+fn sql_type(kind: ColumnKind) -> &'static str {
+    match kind {
+        ColumnKind::String => "TEXT",
+        ColumnKind::Float32 => "REAL",
+        ColumnKind::Bool
+            | ColumnKind::Int8
+            | ColumnKind::UInt8
+            | ColumnKind::Int16
+            | ColumnKind::UInt16
+            | ColumnKind::Int32
+            | ColumnKind::UInt32
+            | ColumnKind::Int64
+            | ColumnKind::UInt64
+            | ColumnKind::PackedBool0
+            | ColumnKind::PackedBool1
+            | ColumnKind::PackedBool2
+            | ColumnKind::PackedBool3
+            | ColumnKind::PackedBool4
+            | ColumnKind::PackedBool5
+            | ColumnKind::PackedBool6
+            | ColumnKind::PackedBool7 => "INTEGER"
+    }
+}
+
+fn sql_value(field: &Field) -> SqlValue {
+    match field {
+        Field::String(s) => SqlValue::Text(s.to_string()),
+        Field::Bool(b) => SqlValue::Integer(*b as i64),
+        Field::I8(num) => SqlValue::Integer(*num as i64),
+        Field::I16(num) => SqlValue::Integer(*num as i64),
+        Field::I32(num) => SqlValue::Integer(*num as i64),
+        Field::I64(num) => SqlValue::Integer(*num),
+        Field::U8(num) => SqlValue::Integer(*num as i64),
+        Field::U16(num) => SqlValue::Integer(*num as i64),
+        Field::U32(num) => SqlValue::Integer(*num as i64),
+        // SQLite integers are signed, so the largest values wrap around.
+        Field::U64(num) => SqlValue::Integer(*num as i64),
+        Field::F32(num) => SqlValue::Real(*num as f64)
+    }
+}
+
+/// Quotes an SQL identifier.
+fn quote(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
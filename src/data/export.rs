@@ -0,0 +1,269 @@
+use indexmap::IndexMap;
+use ironworks::excel::SheetIterator;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
+use crate::err::Err;
+use super::format::{flatten, write_definition_list, Table, TabularResult};
+use super::sheet_extractor::{json_key, RowExtractor};
+use super::schema::SheetSchema;
+use super::sheets::Sheet;
+use super::{write_json, write_json_lines, IronworksCli, KeyValues, WritableResult};
+
+impl IronworksCli {
+    /// Exports every row of the given sheet, extracting the same columns
+    /// and links as [`IronworksCli::get()`].
+    ///
+    /// Rows are only read from the game files as the returned iterator
+    /// is advanced, so memory usage does not grow with the size of the sheet.
+    pub fn export<'a>(&'a self, sheet: &'a Sheet) -> Result<SheetRows<'a>, Err> {
+        let sheet_info = self.get_sheet(sheet.name())?;
+        let extractor = self.row_extractor(&sheet_info)?;
+
+        Ok(SheetRows { rows: self.sheet_iter(sheet.name())?, extractor, has_subrows: sheet_info.has_subrows()?, schema: None })
+    }
+
+    /// Extracts multiple rows from the given sheet at once, extracting the same
+    /// columns and links as [`IronworksCli::get()`].
+    ///
+    /// IDs that don't have a corresponding row in the sheet are skipped, so that
+    /// ranges of IDs may contain gaps. For subrow sheets, all subrows of each row
+    /// are extracted.
+    pub fn get_rows(&self, sheet: Sheet, ids: &[u32]) -> Result<Rows, Err> {
+        let sheet_info = self.get_sheet(sheet.name())?;
+        let extractor = self.row_extractor(&sheet_info)?;
+        let mut rows = Vec::with_capacity(ids.len());
+
+        if sheet_info.has_subrows()? {
+            for id in ids {
+                rows.append(&mut extractor.extract_subrows(&sheet_info.sheet, *id)?);
+            }
+        } else {
+            for id in ids {
+                if let Ok(row) = sheet_info.sheet.row(*id) {
+                    rows.push(SheetRow { id: *id, subrow_id: None, values: extractor.extract(&row)? });
+                }
+            }
+        }
+
+        Ok(rows)
+    }
+}
+
+/// A single row (or subrow) of a sheet, including its ID.
+#[derive(Debug)]
+pub struct SheetRow<'a> {
+    /// The ID (or row index) of the row.
+    pub id: u32,
+    /// The subrow ID of the row. This is [`None`] if the sheet has no subrows.
+    pub subrow_id: Option<u16>,
+    /// The extracted columns of the row.
+    pub values: KeyValues<'a>
+}
+
+/// The first line of newline-delimited output that includes the schema.
+#[derive(Serialize)]
+struct WithoutData {
+    schema: SheetSchema
+}
+
+/// Indents every line but the first by the given indentation.
+/// Serialized JSON never contains raw newlines within strings, so this is safe.
+fn indent_lines(json: &str, indent: &str) -> String {
+    json.replace('\n', &format!("\n{}", indent))
+}
+
+fn json_err(err: serde_json::Error) -> Err {
+    Err::IoError(err.into())
+}
+
+/// Multiple rows of a sheet, as returned by [`IronworksCli::get_rows()`].
+pub type Rows<'a> = Vec<SheetRow<'a>>;
+
+/// An iterator over every row of a sheet, as returned by [`IronworksCli::export()`].
+pub struct SheetRows<'a> {
+    rows: SheetIterator<&'a str>,
+    extractor: RowExtractor<'a>,
+    has_subrows: bool,
+    schema: Option<SheetSchema>
+}
+
+impl <'a> Iterator for SheetRows<'a> {
+    type Item = Result<SheetRow<'static>, Err>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let row = self.rows.next()?;
+
+        let subrow_id = self.has_subrows.then(|| row.subrow_id());
+
+        Some(self.extractor.extract(&row).map(|values| SheetRow { id: row.row_id(), subrow_id, values }))
+    }
+}
+
+impl <'a> SheetRows<'a> {
+    /// Includes the given schema in JSON output, as returned by [`IronworksCli::sheet_schema()`].
+    ///
+    /// [`SheetRows::write_all()`] then writes an object with the keys `schema` and `data`
+    /// instead of an array, and [`SheetRows::write_lines()`] writes the schema on the first line.
+    pub fn with_schema(mut self, schema: SheetSchema) -> Self {
+        self.schema = Some(schema);
+
+        self
+    }
+
+    /// Writes all remaining rows as a JSON array to the [`std::io::Write`] stream.
+    /// Each row is written as soon as it is read.
+    pub fn write_all(mut self, mut w: impl std::io::Write, pretty: bool) -> Result<(), Err> {
+        let schema = self.schema.take();
+        let indent = if schema.is_some() && pretty { "  " } else { "" };
+
+        let result = match (&schema, pretty) {
+            (Some(schema), true) => write!(w, "{{\n  \"schema\": {},\n  \"data\": [", indent_lines(&serde_json::to_string_pretty(schema).map_err(json_err)?, "  ")),
+            (Some(schema), false) => write!(w, "{{\"schema\":{},\"data\":[", serde_json::to_string(schema).map_err(json_err)?),
+            (None, _) => write!(w, "[")
+        };
+
+        result.map_err(Err::IoError)?;
+
+        for (i, row) in self.enumerate() {
+            let row = row?;
+
+            if i != 0 {
+                write!(w, ",").map_err(Err::IoError)?;
+            }
+
+            if pretty {
+                // Rows are indented by one level to line up as elements of the array.
+                write!(w, "\n{}  {}", indent, indent_lines(&serde_json::to_string_pretty(&row).map_err(json_err)?, &format!("{}  ", indent))).map_err(Err::IoError)?;
+            } else {
+                serde_json::to_writer(&mut w, &row).map_err(json_err)?;
+            }
+        }
+
+        if pretty {
+            write!(w, "\n{}]", indent).map_err(Err::IoError)?;
+        } else {
+            write!(w, "]").map_err(Err::IoError)?;
+        }
+
+        let result = match (schema, pretty) {
+            (Some(_), true) => writeln!(w, "\n}}"),
+            (Some(_), false) => writeln!(w, "}}"),
+            (None, _) => writeln!(w)
+        };
+
+        result.map_err(Err::IoError)?;
+        w.flush().map_err(Err::IoError)
+    }
+
+    /// Writes all remaining rows as newline-delimited JSON to the [`std::io::Write`] stream,
+    /// i.e. one minified JSON object per line. Each row is written as soon as it is read.
+    pub fn write_lines(mut self, mut w: impl std::io::Write) -> Result<(), Err> {
+        if let Some(schema) = self.schema.take() {
+            write_json(&WithoutData { schema }, &mut w, false).map_err(Err::IoError)?;
+        }
+
+        for row in self {
+            write_json(&row?, &mut w, false).map_err(Err::IoError)?;
+        }
+
+        w.flush().map_err(Err::IoError)
+    }
+
+    /// Writes all remaining rows as a table to the [`std::io::Write`] stream,
+    /// separating cells by the given delimiter.
+    ///
+    /// Unlike [`SheetRows::write_all()`], all rows are read before anything is written,
+    /// since every row may add columns to the table (e.g. rows that reference other sheets).
+    pub fn write_table(self, mut w: impl std::io::Write, delimiter: u8) -> Result<(), Err> {
+        let rows = self.map(|row| row.map(|x| x.cells())).collect::<Result<Vec<_>, Err>>()?;
+
+        Table::from_rows(rows).write(&mut w, delimiter).map_err(Err::IoError)?;
+        w.flush().map_err(Err::IoError)
+    }
+
+    /// Writes all remaining rows as a Markdown table to the [`std::io::Write`] stream, escaping
+    /// the cells if `escape` is set (see [`TabularResult::write_markdown()`]).
+    /// Like [`SheetRows::write_table()`], all rows are read before anything is written.
+    pub fn write_markdown(self, mut w: impl std::io::Write, escape: bool) -> Result<(), Err> {
+        let rows = self.map(|row| row.map(|x| x.cells())).collect::<Result<Vec<_>, Err>>()?;
+        let table = Table::from_rows(rows);
+        let table = if escape { table.escaped() } else { table };
+
+        table.write_markdown(&mut w).map_err(Err::IoError)?;
+        w.flush().map_err(Err::IoError)
+    }
+}
+
+impl <'a> SheetRow<'a> {
+    /// Flattens the row into cells, starting with its ID (and subrow ID).
+    fn cells(&self) -> IndexMap<String, String> {
+        let mut cells = IndexMap::new();
+        cells.insert("Id".to_owned(), self.id.to_string());
+
+        if let Some(subrow_id) = self.subrow_id {
+            cells.insert("SubrowId".to_owned(), subrow_id.to_string());
+        }
+
+        flatten(&self.values, "", &mut cells);
+
+        cells
+    }
+}
+
+impl <'a> Serialize for SheetRow<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("id", &self.id)?;
+
+        if let Some(subrow_id) = self.subrow_id {
+            map.serialize_entry("subrowId", &subrow_id)?;
+        }
+
+        for (key, value) in self.values.iter() {
+            map.serialize_entry(&json_key(key), value)?;
+        }
+
+        map.end()
+    }
+}
+
+impl <'a> WritableResult for Rows<'a> {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, false)
+    }
+
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+
+    fn write_lines(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json_lines(self.iter(), w)
+    }
+}
+
+impl <'a> WritableResult for SheetRow<'a> {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, false)
+    }
+
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+}
+
+impl <'a> TabularResult for Rows<'a> {
+    fn table(&self) -> Table {
+        Table::from_rows(self.iter().map(SheetRow::cells))
+    }
+}
+
+impl <'a> TabularResult for SheetRow<'a> {
+    fn table(&self) -> Table {
+        Table::from_rows([self.cells()])
+    }
+
+    /// A single row is written as a definition list instead of a table with a single record.
+    fn write_markdown(&self, w: impl std::io::Write, escape: bool) -> std::io::Result<()> {
+        write_definition_list(&self.cells(), escape, w)
+    }
+}
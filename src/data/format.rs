@@ -0,0 +1,249 @@
+use indexmap::{IndexMap, IndexSet};
+use ironworks::excel::Field;
+use super::render::escape_markdown;
+use super::{language_code, KeyValues, Value};
+
+/// The format that results are printed in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// A JSON document.
+    #[default]
+    Json,
+    /// Comma-separated values with a header row.
+    Csv,
+    /// Tab-separated values with a header row.
+    Tsv,
+    /// Newline-delimited JSON, i.e. one minified JSON value per line.
+    /// Lists of results (e.g. multiple rows) are written as one line per element.
+    Ndjson,
+    /// Human-readable text. Results without a text representation
+    /// are written as prettified JSON.
+    Text,
+    /// A Markdown table, or a definition list for single rows.
+    /// Strings are rendered as Markdown, see [`crate::SeStringFormat::Markdown`].
+    Markdown
+}
+
+impl OutputFormat {
+    /// Gets the file extension of files in this format, e.g. `json`.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputFormat::Json | OutputFormat::Ndjson => "json",
+            OutputFormat::Csv => "csv",
+            OutputFormat::Tsv => "tsv",
+            OutputFormat::Text => "txt",
+            OutputFormat::Markdown => "md"
+        }
+    }
+
+    /// Gets the delimiter that separates the cells of a record
+    /// if this is a tabular format.
+    pub fn delimiter(&self) -> Option<u8> {
+        match self {
+            OutputFormat::Json | OutputFormat::Ndjson | OutputFormat::Text | OutputFormat::Markdown => None,
+            OutputFormat::Csv => Some(b','),
+            OutputFormat::Tsv => Some(b'\t')
+        }
+    }
+}
+
+/// A result of any of [`crate::IronworksCli`]'s functions that can be
+/// represented as a table, e.g. to be written as CSV.
+pub trait TabularResult {
+    /// Converts the result into a [`Table`].
+    fn table(&self) -> Table;
+    /// Writes the result as Markdown to the [`std::io::Write`] stream.
+    /// By default, the result is written as a Markdown table (see [`Table::write_markdown()`]).
+    ///
+    /// If `escape` is set, cells are escaped first, since strings haven't been
+    /// rendered as Markdown (see [`crate::SeStringFormat::Markdown`]).
+    fn write_markdown(&self, w: impl std::io::Write, escape: bool) -> std::io::Result<()> {
+        let table = self.table();
+        let table = if escape { table.escaped() } else { table };
+
+        table.write_markdown(w)
+    }
+}
+
+/// A table of text cells with a header row.
+#[derive(Debug, Default)]
+pub struct Table {
+    header: Vec<String>,
+    records: Vec<Vec<String>>
+}
+
+impl Table {
+    /// Creates an empty table with the given column names.
+    pub fn new(header: Vec<String>) -> Self {
+        Self { header, records: Vec::new() }
+    }
+
+    /// Appends a record. Its cells must be in the same order as the header.
+    pub fn push(&mut self, record: Vec<String>) {
+        self.records.push(record);
+    }
+
+    /// Creates a table from flattened rows (see [`flatten()`]).
+    ///
+    /// The columns are ordered by their first appearance; rows that don't
+    /// have a value for a column leave its cell empty.
+    pub(crate) fn from_rows(rows: impl IntoIterator<Item = IndexMap<String, String>>) -> Self {
+        let rows: Vec<_> = rows.into_iter().collect();
+        let columns: IndexSet<&str> = rows.iter().flat_map(|x| x.keys().map(String::as_str)).collect();
+
+        let records = rows.iter()
+            .map(|row| columns.iter().map(|x| row.get(*x).cloned().unwrap_or_default()).collect())
+            .collect();
+
+        Self { header: columns.iter().map(|x| x.to_string()).collect(), records }
+    }
+
+    /// Escapes every cell for use within Markdown (the header is always escaped by
+    /// [`Table::write_markdown()`]), e.g. if the cells are plain text rather than Markdown.
+    pub fn escaped(self) -> Self {
+        let records = self.records.iter().map(|x| x.iter().map(|x| escape_markdown(x)).collect()).collect();
+
+        Self { header: self.header, records }
+    }
+
+    /// Writes the table to the [`std::io::Write`] stream, separating cells by the given delimiter.
+    /// Cells are quoted wherever necessary.
+    pub fn write(&self, w: impl std::io::Write, delimiter: u8) -> std::io::Result<()> {
+        let mut writer = csv::WriterBuilder::new().delimiter(delimiter).from_writer(w);
+        writer.write_record(&self.header)?;
+
+        for record in self.records.iter() {
+            writer.write_record(record)?;
+        }
+
+        writer.flush()
+    }
+
+    /// Writes the table as a Markdown table to the [`std::io::Write`] stream.
+    ///
+    /// Cells are expected to be Markdown already (e.g. strings rendered with
+    /// [`crate::SeStringFormat::Markdown`] or cells of [`Table::escaped()`]). Only pipes
+    /// that aren't escaped yet are escaped and line breaks are replaced by `<br>`.
+    /// Column names are plain text, so the header is escaped entirely.
+    pub fn write_markdown(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
+        let row = |cells: &[String]| format!("| {} |", cells.iter().map(|x| markdown_cell(x)).collect::<Vec<_>>().join(" | "));
+        let header: Vec<String> = self.header.iter().map(|x| escape_markdown(x)).collect();
+
+        writeln!(w, "{}", row(&header))?;
+        writeln!(w, "|{}", " --- |".repeat(self.header.len()))?;
+
+        for record in self.records.iter() {
+            writeln!(w, "{}", row(record))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Writes the cells as a Markdown definition list, with the names of the cells as terms.
+/// Cells are expected to be Markdown already, like in [`Table::write_markdown()`], unless `escape` is set.
+pub(crate) fn write_definition_list(cells: &IndexMap<String, String>, escape: bool, mut w: impl std::io::Write) -> std::io::Result<()> {
+    for (i, (name, value)) in cells.iter().enumerate() {
+        if i != 0 {
+            writeln!(w)?;
+        }
+
+        let value = if escape { escape_markdown(value) } else { value.clone() };
+
+        writeln!(w, "{}", escape_markdown(name))?;
+        writeln!(w, ": {}", markdown_cell(&value))?;
+    }
+
+    Ok(())
+}
+
+/// Escapes the unescaped pipes of a Markdown cell and replaces its line breaks by `<br>`.
+fn markdown_cell(cell: &str) -> String {
+    let mut result = String::with_capacity(cell.len());
+    let mut escaped = false;
+
+    for c in cell.chars() {
+        match c {
+            '|' if !escaped => result.push_str("\\|"),
+            '\r' => (),
+            '\n' => result.push_str("<br>"),
+            c => result.push(c)
+        }
+
+        escaped = c == '\\' && !escaped;
+    }
+
+    result
+}
+
+/// Flattens the key values into a single level of cells. Nested columns
+/// are named after their path, e.g. `ClassJob.Name`, `BaseParam[0]` or `Name.en`.
+pub(crate) fn flatten(values: &KeyValues, prefix: &str, cells: &mut IndexMap<String, String>) {
+    flatten_values(values, prefix, &mut |name, value| {
+        cells.insert(name, value_cell(value));
+    });
+}
+
+/// Calls `f` with the flattened name (see [`flatten()`]) of every field in the key values.
+/// Formatted strings are skipped.
+pub(crate) fn flatten_fields(values: &KeyValues, prefix: &str, f: &mut impl FnMut(String, &Field)) {
+    flatten_values(values, prefix, &mut |name, value| {
+        if let Some(field) = value.as_field() {
+            f(name, field);
+        }
+    });
+}
+
+/// Calls `f` with the flattened name of every value that has no nested values.
+fn flatten_values(values: &KeyValues, prefix: &str, f: &mut impl FnMut(String, &Value)) {
+    for (key, value) in values.iter() {
+        flatten_value(value, format!("{}{}", prefix, key), f);
+    }
+}
+
+fn flatten_value(value: &Value, name: String, f: &mut impl FnMut(String, &Value)) {
+    match value {
+        Value::Field(_) | Value::Tokens(_) | Value::Formatted(_) | Value::Null => f(name, value),
+        Value::Row(values) | Value::Struct(values) => flatten_values(values, &format!("{}.", name), f),
+        Value::Array(elements) => {
+            for (i, element) in elements.iter().enumerate() {
+                flatten_value(element, format!("{}[{}]", name, i), f);
+            }
+        },
+        Value::Localized(values) => {
+            for (language, value) in values.iter() {
+                flatten_value(value, format!("{}.{}", name, language_code(*language)), f);
+            }
+        }
+    }
+}
+
+/// Gets the text of a single cell for a value without nested values.
+/// Other values result in an empty cell.
+pub(crate) fn value_cell(value: &Value) -> String {
+    match value {
+        Value::Field(field) => cell(field),
+        // Token trees are written as JSON, since they can't be represented as a single cell otherwise.
+        Value::Tokens(tokens) => serde_json::to_string(tokens).unwrap_or_default(),
+        Value::Formatted(s) => s.clone(),
+        _ => String::new()
+    }
+}
+
+/// Gets the text of a single cell for the field.
+pub(crate) fn cell(field: &Field) -> String {
+    match field {
+        Field::String(s) => s.to_string(),
+        Field::Bool(b) => b.to_string(),
+        Field::I8(num) => num.to_string(),
+        Field::I16(num) => num.to_string(),
+        Field::I32(num) => num.to_string(),
+        Field::I64(num) => num.to_string(),
+        Field::U8(num) => num.to_string(),
+        Field::U16(num) => num.to_string(),
+        Field::U32(num) => num.to_string(),
+        Field::U64(num) => num.to_string(),
+        Field::F32(num) => num.to_string()
+    }
+}
@@ -17,7 +17,7 @@ pub fn extract(id: u32, game_path: Option<&Path>, mut writer: impl std::io::Writ
 }
 
 /// See https://github.com/xivapi/ffxiv-datamining/blob/master/docs/IconPaths.md
-fn get_icon_path(id: u32) -> String {
+pub(crate) fn get_icon_path(id: u32) -> String {
     let id_str = id.to_string();
 
     let icon = if id_str.len() > 5 {
@@ -1,8 +1,9 @@
-use std::{borrow::Cow, env::current_exe, fs, path::{Path, PathBuf}, sync::Arc};
-use ironworks::{excel::{Excel, Language, Sheet, SheetIterator}, sqpack::{Install, Resource, SqPack}, Ironworks};
+use std::{borrow::Cow, env::current_exe, fs, path::{Path, PathBuf}, sync::{Arc, OnceLock}};
+use ironworks::{excel::{Excel, Language, Sheet, SheetIterator}, file::exh::{ColumnDefinition, SheetKind}, sqpack::{Install, Resource, SqPack}, Ironworks};
 use ironworks_schema::{exdschema::{Provider, Version}, Node, Order, Schema};
 use crate::err::{Err, ToUnknownErr};
-use super::SheetColumn;
+use super::render::{GlobalParameters, UiColors};
+use super::{ColumnNode, PlayerContext, SeStringFormat, SheetColumn};
 
 /// A builder for the main [`IronworksCli`] interface.
 /// This is the entry point of the crate.
@@ -14,7 +15,13 @@ use super::SheetColumn;
 pub struct IronworksBuilder {
     game_path: Option<PathBuf>,
     should_refresh_schema: bool,
-    requested_version: Option<String>
+    requested_version: Option<String>,
+    link_depth: u8,
+    language: Option<Language>,
+    all_languages: bool,
+    sort_keys: bool,
+    sestring_format: SeStringFormat,
+    player_context: Option<PlayerContext>
 }
 
 impl IronworksBuilder {
@@ -57,6 +64,74 @@ impl IronworksBuilder {
         self
     }
 
+    /// Sets how many levels of references to other sheets are resolved.
+    ///
+    /// EXDSchema describes which columns reference rows in other sheets.
+    /// For every level of depth, the values of these columns are replaced
+    /// by the referenced row (e.g. `Action.ClassJob` by a row of the ClassJob sheet)
+    /// instead of its bare ID.
+    ///
+    /// Default is `0`, in which case references are never resolved.
+    pub fn link_depth(mut self, depth: u8) -> Self {
+        self.link_depth = depth;
+
+        self
+    }
+
+    /// Sets the language that all strings are read in.
+    ///
+    /// Default is [`Language::English`]. Note that not every language is available
+    /// in every version of the game; the global client only contains English,
+    /// Japanese, German and French.
+    pub fn language(mut self, language: Language) -> Self {
+        self.language = Some(language);
+
+        self
+    }
+
+    /// Reads every string in all languages available for its sheet, rather than
+    /// only the language set via [`IronworksBuilder::language()`].
+    ///
+    /// Strings are then output as an object keyed by language code,
+    /// e.g. `{ "en": ..., "ja": ..., "de": ..., "fr": ... }`.
+    /// All other fields are unaffected.
+    pub fn all_languages(mut self) -> Self {
+        self.all_languages = true;
+
+        self
+    }
+
+    /// Sorts the keys of every extracted row alphabetically.
+    ///
+    /// By default, keys are ordered like the columns in the sheet's schema,
+    /// followed by the columns of linked sheets.
+    pub fn sort_keys(mut self) -> Self {
+        self.sort_keys = true;
+
+        self
+    }
+
+    /// Specifies how string fields are extracted. Defaults to [`SeStringFormat::Text`].
+    ///
+    /// The format applies to every string field of extracted rows,
+    /// including those of linked rows and localized strings.
+    pub fn sestring_format(mut self, format: SeStringFormat) -> Self {
+        self.sestring_format = format;
+
+        self
+    }
+
+    /// Evaluates conditional text in SeStrings (e.g. level- or job-dependent effects
+    /// in action descriptions) for the given character, so that strings contain
+    /// the text that the character would see in game.
+    ///
+    /// This applies to strings extracted as [`SeStringFormat::Text`] or [`SeStringFormat::Html`].
+    pub fn player_context(mut self, context: PlayerContext) -> Self {
+        self.player_context = Some(context);
+
+        self
+    }
+
     /// Builds an instance of the ironworks CLI.
     /// This function may be expensive to execute, as it will attempt to read
     /// or update the schema (if necessary) and find the FFXIV directory.
@@ -64,15 +139,24 @@ impl IronworksBuilder {
         let game_resource = get_game_resource(self.game_path.as_deref())?;
         let version_string = self.requested_version.unwrap_or_else(|| game_resource.version(0).unwrap());
         let ironworks = Arc::new(Ironworks::new().with_resource(SqPack::new(game_resource)));
-        let excel = Excel::new(ironworks).with_default_language(Language::English);
+        let language = self.language.unwrap_or(Language::English);
+        let excel = Excel::new(ironworks).with_default_language(language);
         let schema = get_schema(&version_string, self.should_refresh_schema)?;
 
-        Ok(IronworksCli { excel, schema, version: version_string })
+        let mut cli = IronworksCli { excel, schema, version: version_string, link_depth: self.link_depth, language, all_languages: self.all_languages, sort_keys: self.sort_keys, sestring_format: self.sestring_format, ui_colors: OnceLock::new(), player_parameters: None };
+
+        // Resolving the job requires the ClassJob sheet, so the parameters can only be determined afterwards.
+        if let Some(context) = self.player_context {
+            cli.player_parameters = Some(cli.global_parameters(context)?);
+        }
+
+        Ok(cli)
     }
 }
 
 #[derive(Debug)]
 pub(crate) struct SheetInfo<'a> {
+    pub name: &'a str,
     pub sheet: Sheet<&'a str>,
     pub schema: ironworks_schema::Sheet
 }
@@ -83,19 +167,48 @@ pub(crate) struct SheetInfo<'a> {
 pub struct IronworksCli {
     excel: Excel,
     schema: Version,
-    version: String
+    version: String,
+    pub(crate) link_depth: u8,
+    language: Language,
+    pub(crate) all_languages: bool,
+    pub(crate) sort_keys: bool,
+    pub(crate) sestring_format: SeStringFormat,
+    /// The colors of the UIColor sheet, which are read when they are first needed.
+    pub(crate) ui_colors: OnceLock<UiColors>,
+    /// The parameters that SeStrings are evaluated with. See [`IronworksBuilder::player_context()`].
+    pub(crate) player_parameters: Option<GlobalParameters>
 }
 
 impl IronworksCli {
     pub(crate) fn get_sheet<'a>(&self, sheet_name: &'a str) -> Result<SheetInfo<'a>, Err> {
+        let sheet = self.excel_sheet(sheet_name)?;
+        let languages = sheet.languages().map_err(|_| Err::UnsupportedSheet(sheet_name.to_owned().into()))?;
+
+        // Sheets that aren't localized only contain `Language::None`, which is
+        // used as a fallback regardless of the requested language.
+        if !languages.contains(&self.language) && !languages.contains(&Language::None) {
+            return Err(Err::LanguageNotFound(sheet_name.to_owned().into(), self.language));
+        }
+
         Ok(SheetInfo {
-            sheet: self.excel.sheet(sheet_name).map_err(|_| Err::SheetNotFound(sheet_name.to_owned().into()))?,
+            name: sheet_name,
+            sheet,
             schema: self.schema.sheet(sheet_name).map_err(|_| Err::SheetNotFound(sheet_name.to_owned().into()))?
         })
     }
 
+    /// Gets a sheet without reading its schema.
+    pub(crate) fn excel_sheet<'a>(&self, sheet_name: &'a str) -> Result<Sheet<&'a str>, Err> {
+        self.excel.sheet(sheet_name).map_err(|_| Err::SheetNotFound(sheet_name.to_owned().into()))
+    }
+
     pub(crate) fn sheet_iter<'a>(&self, sheet_name: &'a str) -> Result<SheetIterator<&'a str>, Err> {
-        Ok(self.excel.sheet(sheet_name).map_err(|_| Err::SheetNotFound(sheet_name.to_owned().into()))?.into_iter())
+        Ok(self.excel_sheet(sheet_name)?.into_iter())
+    }
+
+    /// Gets the language that all strings are read in.
+    pub fn language(&self) -> Language {
+        self.language
     }
 
     /// Gets the game's version.
@@ -134,6 +247,21 @@ pub(crate) fn get_game_resource(game_dir: Option<&Path>) -> Result<Install, Err>
 }
 
 impl <'a> SheetInfo<'a> {
+    /// Whether the sheet is a subrow sheet, i.e. each of its rows consists of multiple subrows.
+    pub(crate) fn has_subrows(&self) -> Result<bool, Err> {
+        let kind = self.sheet.kind().map_err(|_| Err::UnsupportedSheet(Cow::Owned(self.sheet.name())))?;
+
+        Ok(matches!(kind, SheetKind::Subrows))
+    }
+
+    /// Gets all languages that the sheet is localized in.
+    /// This is empty if the sheet isn't localized.
+    pub(crate) fn localized_languages(&self) -> Result<Vec<Language>, Err> {
+        let languages = self.sheet.languages().map_err(|_| Err::UnsupportedSheet(Cow::Owned(self.sheet.name())))?;
+
+        Ok(languages.into_iter().filter(|x| *x != Language::None).collect())
+    }
+
     pub(crate) fn columns(&self) -> Result<impl Iterator<Item = SheetColumn> + use<'_>, Err> {
         self.filtered_columns_iter(None)
     }
@@ -151,7 +279,7 @@ impl <'a> SheetInfo<'a> {
             Order::Offset => columns.sort_by_key(|column| column.offset()),
         };
     
-        Ok(fields.iter()
+        let columns: Vec<SheetColumn> = fields.iter()
             .filter(move |x| {
                 if let Some(filter_columns) = filter_columns {
                     filter_columns.contains(&x.name.as_ref())
@@ -159,6 +287,96 @@ impl <'a> SheetInfo<'a> {
                     true
                 }
             })
-            .map(move |x| SheetColumn { name: x.name.clone(), column: columns[x.offset as usize].clone() }))
+            .map(|x| Some(SheetColumn { name: x.name.clone(), node: column_node(&x.node, x.offset, &columns)? }))
+            .collect::<Option<_>>()
+            .ok_or_else(|| Err::UnsupportedSheet(Cow::Owned(self.sheet.name())))?;
+
+        Ok(columns.into_iter())
+    }
+}
+
+/// Maps a schema node starting at the given column offset to the columns it covers.
+/// Returns [`None`] if the node covers columns that don't exist in the sheet.
+fn column_node<C: Clone>(node: &Node, offset: u32, columns: &[C]) -> Option<ColumnNode<C>> {
+    Some(match node {
+        Node::Array { count, node } => {
+            let size = node_size(node);
+
+            ColumnNode::Array((0..*count).map(|i| column_node(node, offset + i * size, columns)).collect::<Option<_>>()?)
+        },
+        Node::Struct(fields) => ColumnNode::Struct(fields.iter()
+            .map(|x| Some(SheetColumn { name: x.name.clone(), node: column_node(&x.node, offset + x.offset, columns)? }))
+            .collect::<Option<_>>()?),
+        Node::Reference(targets) => ColumnNode::Scalar(columns.get(offset as usize)?.clone(), targets.clone()),
+        _ => ColumnNode::Scalar(columns.get(offset as usize)?.clone(), Vec::new())
+    })
+}
+
+/// Gets the number of columns covered by a schema node.
+fn node_size(node: &Node) -> u32 {
+    match node {
+        Node::Array { count, node } => count * node_size(node),
+        Node::Struct(fields) => fields.iter().map(|x| x.offset + node_size(&x.node)).max().unwrap_or(0),
+        _ => 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ironworks_schema::StructField;
+    use super::*;
+
+    fn leaf() -> Node {
+        Node::Reference(Vec::new())
+    }
+
+    fn array(count: u32, node: Node) -> Node {
+        Node::Array { count, node: Box::new(node) }
+    }
+
+    fn structure(fields: Vec<(&str, u32, Node)>) -> Node {
+        Node::Struct(fields.into_iter().map(|(name, offset, node)| StructField { name: name.to_owned(), offset, node }).collect())
+    }
+
+    /// Describes the offsets of the columns covered by a node, e.g. `[{a:0,b:1}]`.
+    fn layout(node: &ColumnNode<u32>) -> String {
+        match node {
+            ColumnNode::Scalar(offset, _) => offset.to_string(),
+            ColumnNode::Array(nodes) => format!("[{}]", nodes.iter().map(layout).collect::<Vec<_>>().join(",")),
+            ColumnNode::Struct(columns) => format!("{{{}}}", columns.iter().map(|x| format!("{}:{}", x.name, layout(&x.node))).collect::<Vec<_>>().join(","))
+        }
+    }
+
+    fn column_layout(node: &Node, offset: u32, column_count: u32) -> Option<String> {
+        column_node(node, offset, &(0..column_count).collect::<Vec<_>>()).as_ref().map(layout)
+    }
+
+    #[test]
+    fn node_size_counts_nested_columns() {
+        assert_eq!(node_size(&leaf()), 1);
+        assert_eq!(node_size(&array(3, structure(vec![("a", 0, leaf()), ("b", 1, leaf())]))), 6);
+        assert_eq!(node_size(&structure(vec![("a", 0, leaf()), ("b", 1, array(2, leaf())), ("c", 3, leaf())])), 4);
+        assert_eq!(node_size(&structure(vec![("a", 0, leaf()), ("b", 4, leaf())])), 5);
+        assert_eq!(node_size(&array(0, leaf())), 0);
+    }
+
+    #[test]
+    fn column_node_maps_arrays_of_structs() {
+        let node = array(2, structure(vec![("a", 0, leaf()), ("b", 1, array(2, leaf()))]));
+
+        assert_eq!(column_layout(&node, 3, 10).unwrap(), "[{a:3,b:[4,5]},{a:6,b:[7,8]}]");
+    }
+
+    #[test]
+    fn column_node_maps_nested_structs() {
+        let node = structure(vec![("x", 0, leaf()), ("y", 1, structure(vec![("a", 0, leaf()), ("b", 1, array(2, leaf()))])), ("z", 4, leaf())]);
+
+        assert_eq!(column_layout(&node, 1, 10).unwrap(), "{x:1,y:{a:2,b:[3,4]},z:5}");
+    }
+
+    #[test]
+    fn column_node_rejects_missing_columns() {
+        assert!(column_layout(&array(3, leaf()), 8, 10).is_none());
+        assert_eq!(column_layout(&array(3, leaf()), 7, 10).unwrap(), "[7,8,9]");
     }
 }
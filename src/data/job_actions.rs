@@ -1,17 +1,18 @@
 use std::ops::{Deref, DerefMut};
 
+use serde::{Serialize, Serializer};
 use crate::{data::sheet_extractor::SheetColumn, err::{Err, ToUnknownErr}};
-use super::{role_actions::Role, Id, IronworksCli, WritableResult};
+use super::{role_actions::Role, value_cell, write_json, write_json_lines, Id, IronworksCli, Table, TabularResult, Value, WritableResult};
 
 const SHEET_NAME: &str = "Action";
 const CLASS_JOB_SHEET_NAME: &str = "ClassJob";
 
 /// Represents an action, as returned by [`IronworksCli::get_job_actions()`] or [`IronworksCli::get_role_actions()`].
 /// Use [`IronworksCli::get()`] to retrieve additional information about the action.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Action {
     pub id: u32,
-    pub name: String
+    pub name: Value
 }
 
 /// Represents a list of actions returned by the [`IronworksCli::get_job_actions()`]
@@ -56,8 +57,8 @@ impl IronworksCli {
         let (class_id, base_class_id) = self.get_class_id(class_id)?;
     
         let columns: Vec<SheetColumn> = sheet_info.filtered_columns(&[CLASS_JOB_SHEET_NAME, "Name"])?.collect();
-        let class_job_column = &columns.iter().find(|x| x.name == CLASS_JOB_SHEET_NAME).to_unknown_err(7)?.column;
-        let name_column = &columns.iter().find(|x| &x.name == "Name").to_unknown_err(8)?.column;
+        let class_job_column = columns.iter().find(|x| x.name == CLASS_JOB_SHEET_NAME).and_then(SheetColumn::column).to_unknown_err(7)?;
+        let name_column = columns.iter().find(|x| &x.name == "Name").and_then(SheetColumn::column).to_unknown_err(8)?;
     
         for row in sheet_info.sheet.into_iter() {
             let class_job_id = row.field(class_job_column).to_unknown_err(9)?.into_i8().to_unknown_err(10)?;
@@ -65,7 +66,7 @@ impl IronworksCli {
             if class_job_id == class_id as i8 || class_job_id == base_class_id as i8 {
                 matches.push(Action {
                     id: row.row_id(),
-                    name: row.field(name_column).unwrap().as_string().to_unknown_err(11)?.to_string()
+                    name: self.field_value(row.field(name_column).to_unknown_err(11)?)?
                 });
             }
         }
@@ -80,8 +81,8 @@ impl IronworksCli {
         let categories = role.get_class_categories();
     
         let columns: Vec<SheetColumn> = sheet_info.filtered_columns(&["ClassJobCategory", "Name"])?.collect();
-        let class_job_column = &columns.iter().find(|x| &x.name == "ClassJobCategory").to_unknown_err(12)?.column;
-        let name_column = &columns.iter().find(|x| &x.name == "Name").to_unknown_err(13)?.column;
+        let class_job_column = columns.iter().find(|x| &x.name == "ClassJobCategory").and_then(SheetColumn::column).to_unknown_err(12)?;
+        let name_column = columns.iter().find(|x| &x.name == "Name").and_then(SheetColumn::column).to_unknown_err(13)?;
     
         for row in sheet_info.sheet.into_iter() {
             let class_job_id = row.field(class_job_column).to_unknown_err(15)?.into_u8().to_unknown_err(14)?;
@@ -89,7 +90,7 @@ impl IronworksCli {
             if categories.contains(&class_job_id) {
                 matches.push(Action {
                     id: row.row_id(),
-                    name: row.field(name_column).unwrap().as_string().to_unknown_err(16)?.to_string()
+                    name: self.field_value(row.field(name_column).to_unknown_err(16)?)?
                 });
             }
         }
@@ -104,70 +105,115 @@ impl IronworksCli {
         let class_id = match id {
             Id::Index(id) => id,
             Id::Name(abbreviation) => {
-                let abbreviation_column = &columns.iter().find(|x| x.name == "Abbreviation").to_unknown_err(17)?.column;
+                let abbreviation_column = columns.iter().find(|x| x.name == "Abbreviation").and_then(SheetColumn::column).to_unknown_err(17)?;
                 self.sheet_iter(CLASS_JOB_SHEET_NAME)?
                     .find(|x| x.field(abbreviation_column).unwrap().into_string().unwrap().to_string() == abbreviation)
                     .ok_or_else(|| Err::JobAcronymNotFound(abbreviation.clone()))?.row_id()
-            }
+            },
+            Id::Indices(_) | Id::Subrow(_, _) => Err(Err::UnsupportedId)?
         };
     
-        let base_class_column = &columns.iter().find(|x| x.name == "ClassJobParent").to_unknown_err(18)?.column;
+        let base_class_column = columns.iter().find(|x| x.name == "ClassJobParent").and_then(SheetColumn::column).to_unknown_err(18)?;
         let class_job = sheet_info.sheet.row(class_id).map_err(|_| Err::JobNotFound(class_id))?;
     
         Ok((class_id as u8, class_job.field(base_class_column).to_unknown_err(19)?.into_u8().to_unknown_err(20)?))
     }
 }
 
-impl <'a> WritableResult for WritableActions<'a> {
-    fn write(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
-        write!(w, "[")?;
-    
+impl <'a> Serialize for WritableActions<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         if self.names {
-            for (i, action) in self.actions.iter().enumerate() {
-                if i != 0 {
-                    write!(w, ",")?;
-                }
-    
-                write!(w, "{{\"id\":{},\"name\":{}}}", &action.id, &action.name)?;
-            }
+            self.actions.serialize(serializer)
         } else {
-            for (i, action) in self.actions.iter().enumerate() {
-                if i != 0 {
-                    write!(w, ",")?;
-                }
-    
-                write!(w, "{}", action.id)?;
-            }
+            serializer.collect_seq(self.actions.iter().map(|x| x.id))
         }
-    
-        writeln!(w, "]")?;
+    }
+}
 
-        Ok(())
+impl <'a> WritableResult for WritableActions<'a> {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, false)
     }
     
-    fn pretty_write(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
-        writeln!(w, "[")?;
-    
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+
+    fn write_lines(&self, w: impl std::io::Write) -> std::io::Result<()> {
         if self.names {
-            for (i, action) in self.actions.iter().enumerate() {
-                if i != 0 {
-                    writeln!(w, ",")?;
-                }
-    
-                write!(w, "  {{ \"id\": {}, \"name\": {} }}", &action.id, &action.name)?;
-            }
+            write_json_lines(self.actions.iter(), w)
         } else {
-            for (i, action) in self.actions.iter().enumerate() {
-                if i != 0 {
-                    writeln!(w, ",")?;
-                }
-    
-                write!(w, "  {}", action.id)?;
-            }
+            write_json_lines(self.actions.iter().map(|x| x.id), w)
         }
-    
-        writeln!(w, "\n]")?;
+    }
+}
+
+impl <'a> TabularResult for WritableActions<'a> {
+    fn table(&self) -> Table {
+        if self.names {
+            let mut table = Table::new(vec!["Id".to_owned(), "Name".to_owned()]);
+            self.actions.iter().for_each(|x| table.push(vec![x.id.to_string(), value_cell(&x.name)]));
+
+            table
+        } else {
+            let mut table = Table::new(vec!["Id".to_owned()]);
+            self.actions.iter().for_each(|x| table.push(vec![x.id.to_string()]));
+
+            table
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRICKY_NAMES: [&str; 4] = ["\"Quoted\" Strike", "Back\\slash", "Line\nBreak\ttab", "Sprint\u{1}"];
+
+    fn actions() -> Actions {
+        Actions(TRICKY_NAMES.iter().enumerate().map(|(i, name)| Action { id: i as u32, name: Value::Formatted(name.to_string()) }).collect())
+    }
+
+    #[test]
+    fn action_names_round_trip() {
+        for pretty in [false, true] {
+            let mut output = Vec::new();
+            let actions = actions();
+            let result = if pretty { actions.writable(true).pretty_write(&mut output) } else { actions.writable(true).write(&mut output) };
+            result.unwrap();
+
+            let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+            let names: Vec<&str> = parsed.as_array().unwrap().iter().map(|x| x["name"].as_str().unwrap()).collect();
+
+            assert_eq!(names, TRICKY_NAMES);
+        }
+    }
+
+    #[test]
+    fn action_ids_round_trip() {
+        let mut output = Vec::new();
+        actions().writable(false).write(&mut output).unwrap();
+
+        let parsed: Vec<u32> = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(parsed, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn action_names_markdown_table() {
+        let mut output = Vec::new();
+        actions().writable(true).write_markdown(&mut output, true).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
 
-        Ok(())
+        assert_eq!(lines, [
+            "| Id | Name |",
+            "| --- | --- |",
+            "| 0 | \"Quoted\" Strike |",
+            "| 1 | Back\\\\slash |",
+            "| 2 | Line<br>Break\ttab |",
+            "| 3 | Sprint\u{1} |"
+        ]);
     }
 }
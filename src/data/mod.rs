@@ -1,23 +1,60 @@
+mod class_jobs;
+mod database;
+mod export;
+mod format;
 mod icons;
 mod init;
 mod job_actions;
+mod parquet;
+mod render;
 mod role_actions;
+mod schema;
+mod sestring;
 mod sheet_extractor;
 mod sheets;
+mod template;
 
+pub use class_jobs::*;
+pub use database::*;
+pub use export::*;
+pub use format::*;
 pub use init::*;
 pub use icons::extract as extract_icon;
+pub use ironworks::excel::Language;
 use ironworks::{excel::Field, sestring::SeString};
+use serde::{Serialize, Serializer};
 pub use job_actions::*;
 pub use role_actions::*;
+pub use schema::*;
+pub use sestring::*;
 pub use sheet_extractor::*;
 pub use sheets::*;
+pub use template::*;
 
 /// Either the name or numerical ID of the desired entity.
 #[derive(Debug, Clone)]
 pub enum Id {
     Name(String),
-    Index(u32)
+    Index(u32),
+    /// Multiple numerical IDs, e.g. from a range or a list of IDs.
+    Indices(Vec<u32>),
+    /// The numerical ID of a row and one of its subrows.
+    Subrow(u32, u16)
+}
+
+/// Gets the code that identifies the language in output, e.g. `en` for English.
+pub(crate) fn language_code(language: Language) -> &'static str {
+    match language {
+        Language::None => "none",
+        Language::Japanese => "ja",
+        Language::English => "en",
+        Language::German => "de",
+        Language::French => "fr",
+        Language::ChineseSimplified => "chs",
+        Language::Korean => "ko",
+        // Languages that aren't supported by `--language`, e.g. traditional Chinese.
+        _ => "unknown"
+    }
 }
 
 /// A result of any of [`IronworksCli`]'s functions that can be written
@@ -27,36 +64,85 @@ pub trait WritableResult {
     fn write(&self, w: impl std::io::Write) -> std::io::Result<()>;
     /// Writes a prettified representation (with whitespace) of the result to the [`std::io::Write`] stream.
     fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()>;
+    /// Writes the result as newline-delimited JSON to the [`std::io::Write`] stream.
+    /// Results that consist of multiple elements are written as one line per element.
+    fn write_lines(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        self.write(w)
+    }
+    /// Writes a human-readable text representation of the result to the [`std::io::Write`] stream.
+    /// Results without a dedicated text representation are written as prettified JSON.
+    fn write_text(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        self.pretty_write(w)
+    }
 }
 
-impl WritableResult for Field {
-    fn write(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
-        match self {
-            Field::String(s) => s.write(w),
-            Field::Bool(b) => write!(w, "{}", b),
-            Field::I8(num) => write!(w, "{}", num),
-            Field::I16(num) => write!(w, "{}", num),
-            Field::I32(num) => write!(w, "{}", num),
-            Field::I64(num) => write!(w, "{}", num),
-            Field::U8(num) => write!(w, "{}", num),
-            Field::U16(num) => write!(w, "{}", num),
-            Field::U32(num) => write!(w, "{}", num),
-            Field::U64(num) => write!(w, "{}", num),
-            Field::F32(num) => write!(w, "{}", num)
+/// Writes the value as JSON to the [`std::io::Write`] stream, followed by a newline.
+pub(crate) fn write_json(value: &impl Serialize, mut w: impl std::io::Write, pretty: bool) -> std::io::Result<()> {
+    if pretty {
+        serde_json::to_writer_pretty(&mut w, value)?;
+    } else {
+        serde_json::to_writer(&mut w, value)?;
+    }
+
+    writeln!(w)
+}
+
+/// Writes each element as minified JSON on its own line.
+pub(crate) fn write_json_lines<T: Serialize>(elements: impl IntoIterator<Item = T>, mut w: impl std::io::Write) -> std::io::Result<()> {
+    for element in elements {
+        write_json(&element, &mut w, false)?;
+    }
+
+    Ok(())
+}
+
+/// Wraps a [`Field`] so that it can be serialized as a plain JSON value.
+pub(crate) struct SerializableField<'a>(pub &'a Field);
+
+impl <'a> Serialize for SerializableField<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.0 {
+            Field::String(s) => serialize_sestring(s, serializer),
+            Field::Bool(b) => serializer.serialize_bool(*b),
+            Field::I8(num) => serializer.serialize_i8(*num),
+            Field::I16(num) => serializer.serialize_i16(*num),
+            Field::I32(num) => serializer.serialize_i32(*num),
+            Field::I64(num) => serializer.serialize_i64(*num),
+            Field::U8(num) => serializer.serialize_u8(*num),
+            Field::U16(num) => serializer.serialize_u16(*num),
+            Field::U32(num) => serializer.serialize_u32(*num),
+            Field::U64(num) => serializer.serialize_u64(*num),
+            Field::F32(num) => serializer.serialize_f32(*num)
         }
     }
+}
+
+/// Serializes a [`Field`]. For use with `#[serde(serialize_with)]`.
+pub(crate) fn serialize_field<S: Serializer>(field: &Field, serializer: S) -> Result<S::Ok, S::Error> {
+    SerializableField(field).serialize(serializer)
+}
+
+/// Serializes a [`SeString`] as its plain text. For use with `#[serde(serialize_with)]`.
+pub(crate) fn serialize_sestring<S: Serializer>(s: &SeString, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&s.to_string())
+}
+
+impl WritableResult for Field {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(&SerializableField(self), w, false)
+    }
 
     fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
-        self.write(w)
+        write_json(&SerializableField(self), w, true)
     }
 }
 
 impl <'a> WritableResult for SeString<'a> {
-    fn write(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
-        write!(w, "\"{}\"", self.to_string().replace('\n', "\\n").replace('"', "\\\""))
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(&self.to_string(), w, false)
     }
 
     fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
-        self.write(w)
+        write_json(&self.to_string(), w, true)
     }
 }
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use arrow_array::builder::{ArrayBuilder, BooleanBuilder, Float32Builder, Int16Builder, Int32Builder, Int64Builder, Int8Builder, StringBuilder, UInt16Builder, UInt32Builder, UInt64Builder, UInt8Builder};
+use arrow_array::{ArrayRef, RecordBatch};
+use arrow_schema::{DataType, Field as ArrowField, Schema};
+use ironworks::excel::Field;
+use ironworks::file::exh::ColumnKind;
+use parquet::arrow::ArrowWriter;
+use parquet::basic::Compression;
+use parquet::file::properties::WriterProperties;
+use crate::err::Err;
+use super::format::flatten_fields;
+use super::sheets::Sheet;
+use super::IronworksCli;
+
+/// The number of rows that are buffered before they are written as a row group.
+const BATCH_SIZE: usize = 8192;
+
+impl IronworksCli {
+    /// Writes every row of the given sheet as a Parquet file to the [`std::io::Write`] stream.
+    ///
+    /// The same columns and links as in [`IronworksCli::get()`] are written, with nested
+    /// columns flattened (e.g. `BaseParam[0]`), preceded by an `id` (and `subrow_id`) column.
+    /// Like [`IronworksCli::write_database()`], fields are written as they are stored
+    /// in the sheet, so references aren't resolved. Strings are written as UTF-8 columns.
+    pub fn write_parquet(&self, sheet: &Sheet, w: impl std::io::Write + Send) -> Result<(), Err> {
+        let sheet_info = self.get_sheet(sheet.name())?;
+        let extractor = self.raw_row_extractor(&sheet_info)?;
+        let has_subrows = sheet_info.has_subrows()?;
+        let columns = extractor.flat_columns();
+        let column_indices: HashMap<&str, usize> = columns.iter().enumerate().map(|(i, x)| (x.name.as_str(), i)).collect();
+
+        let mut builders: Vec<ColumnBuilder> = columns.iter().map(|x| ColumnBuilder::new(x.column.kind())).collect();
+        let mut ids = UInt32Builder::new();
+        let mut subrow_ids = UInt16Builder::new();
+
+        let mut fields = vec![ArrowField::new("id", DataType::UInt32, false)];
+
+        if has_subrows {
+            fields.push(ArrowField::new("subrow_id", DataType::UInt16, false));
+        }
+
+        fields.extend(columns.iter().zip(builders.iter()).map(|(column, builder)| ArrowField::new(&column.name, builder.data_type(), true)));
+
+        let schema = Arc::new(Schema::new(fields));
+        let properties = WriterProperties::builder().set_compression(Compression::SNAPPY).build();
+        let mut writer = ArrowWriter::try_new(w, schema.clone(), Some(properties)).map_err(Err::ParquetError)?;
+        let mut appended = vec![false; columns.len()];
+
+        for row in sheet_info.sheet.into_iter() {
+            let values = extractor.extract(&row)?;
+            ids.append_value(row.row_id());
+
+            if has_subrows {
+                subrow_ids.append_value(row.subrow_id());
+            }
+
+            appended.fill(false);
+
+            flatten_fields(&values, "", &mut |name, field| {
+                if let Some(&i) = column_indices.get(name.as_str()) {
+                    builders[i].append(Some(field));
+                    appended[i] = true;
+                }
+            });
+
+            // Every column needs a value for every row, so missing columns are null.
+            for (builder, _) in builders.iter_mut().zip(appended.iter()).filter(|(_, appended)| !**appended) {
+                builder.append(None);
+            }
+
+            if ids.len() >= BATCH_SIZE {
+                write_batch(&mut writer, &schema, &mut ids, has_subrows.then_some(&mut subrow_ids), &mut builders)?;
+            }
+        }
+
+        if !ids.is_empty() {
+            write_batch(&mut writer, &schema, &mut ids, has_subrows.then_some(&mut subrow_ids), &mut builders)?;
+        }
+
+        writer.close().map_err(Err::ParquetError)?;
+
+        Ok(())
+    }
+}
+
+fn write_batch(writer: &mut ArrowWriter<impl std::io::Write + Send>, schema: &Arc<Schema>, ids: &mut UInt32Builder, subrow_ids: Option<&mut UInt16Builder>, builders: &mut [ColumnBuilder]) -> Result<(), Err> {
+    let mut arrays: Vec<ArrayRef> = vec![Arc::new(ids.finish())];
+
+    if let Some(subrow_ids) = subrow_ids {
+        arrays.push(Arc::new(subrow_ids.finish()));
+    }
+
+    arrays.extend(builders.iter_mut().map(ColumnBuilder::finish));
+
+    let batch = RecordBatch::try_new(schema.clone(), arrays).map_err(|x| Err::ParquetError(x.into()))?;
+
+    writer.write(&batch).map_err(Err::ParquetError)
+}
+
+/// Builds an Arrow array from the fields of a single column.
+enum ColumnBuilder {
+    String(StringBuilder),
+    Bool(BooleanBuilder),
+    I8(Int8Builder),
+    I16(Int16Builder),
+    I32(Int32Builder),
+    I64(Int64Builder),
+    U8(UInt8Builder),
+    U16(UInt16Builder),
+    U32(UInt32Builder),
+    U64(UInt64Builder),
+    F32(Float32Builder)
+}
+
+impl ColumnBuilder {
+    fn new(kind: ColumnKind) -> Self {
+        match kind {
+            ColumnKind::String => ColumnBuilder::String(StringBuilder::new()),
+            ColumnKind::Int8 => ColumnBuilder::I8(Int8Builder::new()),
+            ColumnKind::UInt8 => ColumnBuilder::U8(UInt8Builder::new()),
+            ColumnKind::Int16 => ColumnBuilder::I16(Int16Builder::new()),
+            ColumnKind::UInt16 => ColumnBuilder::U16(UInt16Builder::new()),
+            ColumnKind::Int32 => ColumnBuilder::I32(Int32Builder::new()),
+            ColumnKind::UInt32 => ColumnBuilder::U32(UInt32Builder::new()),
+            ColumnKind::Float32 => ColumnBuilder::F32(Float32Builder::new()),
+            ColumnKind::Int64 => ColumnBuilder::I64(Int64Builder::new()),
+            ColumnKind::UInt64 => ColumnBuilder::U64(UInt64Builder::new()),
+            ColumnKind::Bool
+                | ColumnKind::PackedBool0
+                | ColumnKind::PackedBool1
+                | ColumnKind::PackedBool2
+                | ColumnKind::PackedBool3
+                | ColumnKind::PackedBool4
+                | ColumnKind::PackedBool5
+                | ColumnKind::PackedBool6
+                | ColumnKind::PackedBool7 => ColumnBuilder::Bool(BooleanBuilder::new())
+        }
+    }
+
+    fn data_type(&self) -> DataType {
+        match self {
+            ColumnBuilder::String(_) => DataType::Utf8,
+            ColumnBuilder::Bool(_) => DataType::Boolean,
+            ColumnBuilder::I8(_) => DataType::Int8,
+            ColumnBuilder::I16(_) => DataType::Int16,
+            ColumnBuilder::I32(_) => DataType::Int32,
+            ColumnBuilder::I64(_) => DataType::Int64,
+            ColumnBuilder::U8(_) => DataType::UInt8,
+            ColumnBuilder::U16(_) => DataType::UInt16,
+            ColumnBuilder::U32(_) => DataType::UInt32,
+            ColumnBuilder::U64(_) => DataType::UInt64,
+            ColumnBuilder::F32(_) => DataType::Float32
+        }
+    }
+
+    /// Appends the field, or a null value if it is missing
+    /// (e.g. a linked column) or doesn't match the column's kind.
+    fn append(&mut self, field: Option<&Field>) {
+        match (self, field) {
+            (ColumnBuilder::String(builder), Some(Field::String(s))) => builder.append_value(s.to_string()),
+            (ColumnBuilder::Bool(builder), Some(Field::Bool(b))) => builder.append_value(*b),
+            (ColumnBuilder::I8(builder), Some(Field::I8(num))) => builder.append_value(*num),
+            (ColumnBuilder::I16(builder), Some(Field::I16(num))) => builder.append_value(*num),
+            (ColumnBuilder::I32(builder), Some(Field::I32(num))) => builder.append_value(*num),
+            (ColumnBuilder::I64(builder), Some(Field::I64(num))) => builder.append_value(*num),
+            (ColumnBuilder::U8(builder), Some(Field::U8(num))) => builder.append_value(*num),
+            (ColumnBuilder::U16(builder), Some(Field::U16(num))) => builder.append_value(*num),
+            (ColumnBuilder::U32(builder), Some(Field::U32(num))) => builder.append_value(*num),
+            (ColumnBuilder::U64(builder), Some(Field::U64(num))) => builder.append_value(*num),
+            (ColumnBuilder::F32(builder), Some(Field::F32(num))) => builder.append_value(*num),
+            (ColumnBuilder::String(builder), _) => builder.append_null(),
+            (ColumnBuilder::Bool(builder), _) => builder.append_null(),
+            (ColumnBuilder::I8(builder), _) => builder.append_null(),
+            (ColumnBuilder::I16(builder), _) => builder.append_null(),
+            (ColumnBuilder::I32(builder), _) => builder.append_null(),
+            (ColumnBuilder::I64(builder), _) => builder.append_null(),
+            (ColumnBuilder::U8(builder), _) => builder.append_null(),
+            (ColumnBuilder::U16(builder), _) => builder.append_null(),
+            (ColumnBuilder::U32(builder), _) => builder.append_null(),
+            (ColumnBuilder::U64(builder), _) => builder.append_null(),
+            (ColumnBuilder::F32(builder), _) => builder.append_null()
+        }
+    }
+
+    /// Builds an array of all appended fields and resets the builder.
+    fn finish(&mut self) -> ArrayRef {
+        match self {
+            ColumnBuilder::String(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::Bool(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::I8(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::I16(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::I32(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::I64(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::U8(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::U16(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::U32(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::U64(builder) => Arc::new(builder.finish()),
+            ColumnBuilder::F32(builder) => Arc::new(builder.finish())
+        }
+    }
+}
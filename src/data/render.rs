@@ -0,0 +1,674 @@
+use std::collections::HashMap;
+use ironworks::sestring::{Expression, MacroKind, Payload, SeString};
+use crate::err::{Err, ToUnknownErr};
+use super::sheet_extractor::{get_u32, SheetColumn};
+use super::IronworksCli;
+
+/// A style that applies to a range of an SeString's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Style {
+    /// The text color as `0xRRGGBB`.
+    Color(u32),
+    /// The color of the text's outline as `0xRRGGBB`.
+    EdgeColor(u32),
+    Italic,
+    Bold
+}
+
+impl Style {
+    fn is_same_kind(&self, other: &Style) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+}
+
+/// Receives the text and styles of an SeString while it is evaluated by [`render()`].
+pub(crate) trait SeStringWriter {
+    fn text(&mut self, text: &str);
+    fn new_line(&mut self);
+    /// A position at which the text may be hyphenated if it is wrapped.
+    fn soft_hyphen(&mut self) {}
+    fn non_breaking_space(&mut self) {
+        self.text("\u{a0}");
+    }
+    fn push_style(&mut self, style: Style);
+    /// Ends the most recently pushed style of the same kind as `style`.
+    /// The value of `style` itself is ignored.
+    fn pop_style(&mut self, style: Style);
+}
+
+/// The information that SeStrings are evaluated with by [`render()`].
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct RenderContext<'a> {
+    /// The colors that `ColorType` and `EdgeColorType` macros refer to.
+    /// If [`None`], these macros are skipped.
+    pub colors: Option<&'a UiColors>,
+    /// The values of global number parameters, e.g. the player's level.
+    /// If [`None`], every parameter is `0`.
+    pub parameters: Option<&'a GlobalParameters>,
+    /// Whether every branch of conditional macros is written instead of the evaluated one,
+    /// separated by spaces, e.g. so that text which depends on the player's level can be searched.
+    pub all_branches: bool
+}
+
+/// The values of the global number parameters that SeStrings can refer to, keyed by index.
+#[derive(Debug, Default)]
+pub(crate) struct GlobalParameters(pub HashMap<u32, u32>);
+
+impl GlobalParameters {
+    /// The index of the parameter that holds the player's gender (`0` for male, `1` for female).
+    pub const GENDER: u32 = 4;
+    /// The index of the parameter that holds the ID of the player's class or job.
+    pub const CLASS_JOB: u32 = 68;
+    /// The index of the parameter that holds the player's level.
+    pub const LEVEL: u32 = 69;
+
+    /// Gets the value of the parameter at the given index, or `0` if it isn't set.
+    pub fn get(&self, index: u32) -> u32 {
+        self.0.get(&index).copied().unwrap_or(0)
+    }
+}
+
+/// The colors of the UIColor sheet as `0xRRGGBB`, keyed by row ID.
+/// `ColorType` and `EdgeColorType` macros both refer to these colors.
+#[derive(Debug, Default)]
+pub(crate) struct UiColors(HashMap<u32, u32>);
+
+/// The UIColor column that holds the colors of the default (dark) theme.
+/// The other columns hold the colors of the other themes, e.g. `Light` or `ClassicFF`.
+const UI_COLOR_THEME_COLUMN: &str = "Dark";
+
+impl IronworksCli {
+    /// Reads the colors that `ColorType` and `EdgeColorType` macros refer to.
+    /// Only the colors of the default (dark) theme are read.
+    pub(crate) fn ui_colors(&self) -> Result<&UiColors, Err> {
+        if let Some(colors) = self.ui_colors.get() {
+            return Ok(colors);
+        }
+
+        let sheet_info = self.get_sheet("UIColor")?;
+        let columns: Vec<SheetColumn> = sheet_info.filtered_columns(&[UI_COLOR_THEME_COLUMN])?.collect();
+        let color_column = columns.iter().find(|x| x.name == UI_COLOR_THEME_COLUMN).and_then(SheetColumn::column)
+            .ok_or_else(|| Err::ColumnNotFound("UIColor".into(), UI_COLOR_THEME_COLUMN))?;
+
+        let mut colors = HashMap::new();
+
+        for row in sheet_info.sheet.into_iter() {
+            let color = row.field(color_column).ok().as_ref().and_then(get_u32).to_unknown_err(46)?;
+
+            // UIColor stores colors as `0xRRGGBBAA`.
+            colors.insert(row.row_id(), color >> 8);
+        }
+
+        Ok(self.ui_colors.get_or_init(|| UiColors(colors)))
+    }
+}
+
+/// Evaluates the SeString and writes its text and styles to the writer.
+///
+/// Conditional macros (`If`, `Switch` and `IfPcGender`) are evaluated with the parameters
+/// of the context, unless [`RenderContext::all_branches`] is set. Parameters that aren't known
+/// are `0`, so that e.g. the male variant of gender-dependent text is used. Macros that have no textual representation
+/// (e.g. icons or links) are skipped.
+pub(crate) fn render(s: &SeString, context: RenderContext, w: &mut impl SeStringWriter) -> Result<(), Err> {
+    for payload in s.payloads() {
+        match payload.map_err(Err::SeStringError)? {
+            Payload::Text(text) => w.text(text.as_utf8().map_err(Err::SeStringError)?),
+            Payload::Macro(payload) => {
+                let arguments = payload.expressions().collect::<Result<Vec<_>, _>>().map_err(Err::SeStringError)?;
+                render_macro(payload.kind(), &arguments, context, w)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn render_macro(kind: MacroKind, arguments: &[Expression], context: RenderContext, w: &mut impl SeStringWriter) -> Result<(), Err> {
+    let number = |expression: &Expression| evaluate(expression, context);
+    let colors = context.colors.map(|x| &x.0);
+
+    match (kind, arguments) {
+        (MacroKind::NewLine, _) => w.new_line(),
+        (MacroKind::SoftHyphen, _) => w.soft_hyphen(),
+        (MacroKind::NonBreakingSpace, _) => w.non_breaking_space(),
+        (MacroKind::Hyphen, _) => w.text("-"),
+        // Colors are stored as `0xAARRGGBB`. `StackColor` restores the previous color.
+        (MacroKind::Color, [Expression::StackColor, ..]) => w.pop_style(Style::Color(0)),
+        (MacroKind::Color, [color, ..]) => w.push_style(Style::Color(number(color) & 0xFFFFFF)),
+        (MacroKind::EdgeColor, [Expression::StackColor, ..]) => w.pop_style(Style::EdgeColor(0)),
+        (MacroKind::EdgeColor, [color, ..]) => w.push_style(Style::EdgeColor(number(color) & 0xFFFFFF)),
+        // Row 0 of UIColor restores the previous color.
+        (MacroKind::ColorType, [id, ..]) => match number(id) {
+            0 => w.pop_style(Style::Color(0)),
+            id => if let Some(color) = colors.and_then(|x| x.get(&id)) {
+                w.push_style(Style::Color(*color));
+            }
+        },
+        (MacroKind::EdgeColorType, [id, ..]) => match number(id) {
+            0 => w.pop_style(Style::EdgeColor(0)),
+            id => if let Some(color) = colors.and_then(|x| x.get(&id)) {
+                w.push_style(Style::EdgeColor(*color));
+            }
+        },
+        (MacroKind::Italic, [enabled, ..]) => toggle_style(Style::Italic, number(enabled) != 0, w),
+        (MacroKind::Bold, [enabled, ..]) => toggle_style(Style::Bold, number(enabled) != 0, w),
+        (MacroKind::If | MacroKind::IfPcGender, [_, then, otherwise, ..]) if context.all_branches => render_branches([then, otherwise], context, w)?,
+        (MacroKind::Switch, [_, cases @ ..]) if context.all_branches => render_branches(cases, context, w)?,
+        (MacroKind::If, [condition, then, otherwise, ..]) => render_expression(if number(condition) != 0 { then } else { otherwise }, context, w)?,
+        (MacroKind::IfPcGender, [_, male, female, ..]) => {
+            let gender = context.parameters.map_or(0, |x| x.get(GlobalParameters::GENDER));
+            render_expression(if gender == 1 { female } else { male }, context, w)?;
+        },
+        // The cases of a switch are numbered starting at 1.
+        (MacroKind::Switch, [value, cases @ ..]) => {
+            if let Some(case) = (number(value) as usize).checked_sub(1).and_then(|x| cases.get(x)) {
+                render_expression(case, context, w)?;
+            }
+        },
+        (MacroKind::Num, [value, ..]) => w.text(&number(value).to_string()),
+        (MacroKind::String, [value, ..]) => render_expression(value, context, w)?,
+        _ => ()
+    }
+
+    Ok(())
+}
+
+fn toggle_style(style: Style, enabled: bool, w: &mut impl SeStringWriter) {
+    if enabled {
+        w.push_style(style);
+    } else {
+        w.pop_style(style);
+    }
+}
+
+/// Writes every branch of a conditional macro, separated by spaces.
+fn render_branches<'e>(branches: impl IntoIterator<Item = &'e Expression>, context: RenderContext, w: &mut impl SeStringWriter) -> Result<(), Err> {
+    for (i, branch) in branches.into_iter().enumerate() {
+        if i != 0 {
+            w.text(" ");
+        }
+
+        render_expression(branch, context, w)?;
+    }
+
+    Ok(())
+}
+
+/// Writes a nested string, or the value of any other expression.
+fn render_expression(expression: &Expression, context: RenderContext, w: &mut impl SeStringWriter) -> Result<(), Err> {
+    match expression {
+        Expression::String(s) => render(s, context, w),
+        expression => {
+            w.text(&evaluate(expression, context).to_string());
+
+            Ok(())
+        }
+    }
+}
+
+/// Evaluates an expression to a number. Values that aren't known (e.g. the time) are `0`.
+fn evaluate(expression: &Expression, context: RenderContext) -> u32 {
+    match expression {
+        Expression::U32(value) => *value,
+        Expression::GlobalNumber(index) => context.parameters.map_or(0, |x| x.get(evaluate(index, context))),
+        Expression::Ge(left, right) => (evaluate(left, context) >= evaluate(right, context)) as u32,
+        Expression::Gt(left, right) => (evaluate(left, context) > evaluate(right, context)) as u32,
+        Expression::Le(left, right) => (evaluate(left, context) <= evaluate(right, context)) as u32,
+        Expression::Lt(left, right) => (evaluate(left, context) < evaluate(right, context)) as u32,
+        Expression::Eq(left, right) => (evaluate(left, context) == evaluate(right, context)) as u32,
+        Expression::Ne(left, right) => (evaluate(left, context) != evaluate(right, context)) as u32,
+        _ => 0
+    }
+}
+
+/// Writes an SeString as plain text, ignoring all styles.
+#[derive(Debug, Default)]
+pub(crate) struct TextWriter {
+    text: String
+}
+
+impl TextWriter {
+    pub fn finish(self) -> String {
+        self.text
+    }
+}
+
+impl SeStringWriter for TextWriter {
+    fn text(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    fn new_line(&mut self) {
+        self.text.push('\n');
+    }
+
+    fn soft_hyphen(&mut self) {
+        self.text.push('\u{ad}');
+    }
+
+    fn push_style(&mut self, _: Style) {}
+
+    fn pop_style(&mut self, _: Style) {}
+}
+
+/// Writes an SeString as normalized plain text, e.g. for search indexing.
+///
+/// Soft hyphens and control characters are removed, except for tabs and other whitespace,
+/// which become regular spaces like non-breaking spaces. Runs of whitespace are collapsed
+/// and empty lines are removed.
+#[derive(Debug, Default)]
+pub(crate) struct PlainWriter {
+    text: String
+}
+
+impl PlainWriter {
+    pub fn finish(self) -> String {
+        self.text.lines()
+            .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+impl SeStringWriter for PlainWriter {
+    fn text(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '\n' => self.text.push('\n'),
+                '\u{ad}' => (),
+                c if c.is_whitespace() => self.text.push(' '),
+                c if c.is_control() => (),
+                c => self.text.push(c)
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.text.push('\n');
+    }
+
+    fn non_breaking_space(&mut self) {
+        self.text.push(' ');
+    }
+
+    fn push_style(&mut self, _: Style) {}
+
+    fn pop_style(&mut self, _: Style) {}
+}
+
+/// Writes an SeString as HTML. Text is escaped, line breaks become `<br>` and
+/// styles become `<span>` elements with inline styles, which are always properly nested.
+#[derive(Debug, Default)]
+pub(crate) struct HtmlWriter {
+    html: String,
+    styles: Vec<Style>
+}
+
+impl HtmlWriter {
+    /// Closes all remaining styles and returns the HTML.
+    pub fn finish(mut self) -> String {
+        self.html.push_str(&"</span>".repeat(self.styles.len()));
+
+        self.html
+    }
+
+    fn open(&mut self, style: Style) {
+        let css = match style {
+            Style::Color(color) => format!("color:#{:06x}", color),
+            Style::EdgeColor(color) => format!("text-shadow:0 0 2px #{:06x}", color),
+            Style::Italic => "font-style:italic".to_owned(),
+            Style::Bold => "font-weight:bold".to_owned()
+        };
+
+        self.html.push_str(&format!("<span style=\"{}\">", css));
+    }
+}
+
+impl SeStringWriter for HtmlWriter {
+    fn text(&mut self, text: &str) {
+        for c in text.chars() {
+            match c {
+                '&' => self.html.push_str("&amp;"),
+                '<' => self.html.push_str("&lt;"),
+                '>' => self.html.push_str("&gt;"),
+                '"' => self.html.push_str("&quot;"),
+                '\'' => self.html.push_str("&#39;"),
+                '\n' => self.new_line(),
+                '\r' => (),
+                c => self.html.push(c)
+            }
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.html.push_str("<br>");
+    }
+
+    fn soft_hyphen(&mut self) {
+        self.html.push_str("&shy;");
+    }
+
+    fn non_breaking_space(&mut self) {
+        self.html.push_str("&nbsp;");
+    }
+
+    fn push_style(&mut self, style: Style) {
+        self.open(style);
+        self.styles.push(style);
+    }
+
+    fn pop_style(&mut self, style: Style) {
+        let Some(index) = self.styles.iter().rposition(|x| x.is_same_kind(&style)) else {
+            return;
+        };
+
+        // Styles that were pushed after the popped one are closed and reopened,
+        // so that the spans stay properly nested.
+        self.html.push_str(&"</span>".repeat(self.styles.len() - index));
+        self.styles.remove(index);
+
+        for style in self.styles[index..].to_vec() {
+            self.open(style);
+        }
+    }
+}
+
+/// Writes an SeString as Markdown. Text is escaped and line breaks become `<br>`,
+/// so that the result can also be used within tables. Colors are ignored.
+///
+/// Italics and emphasis become `*` and `**` wherever CommonMark parses them unambiguously, i.e. around
+/// text that starts and ends with a letter or digit, without emphasis inside and not directly after another
+/// delimiter. Otherwise, e.g. for overlapping styles, they become `<em>` and `<strong>` elements instead.
+#[derive(Debug, Default)]
+pub(crate) struct MarkdownWriter {
+    markdown: String,
+    /// The italic and bold styles that haven't been popped yet.
+    styles: Vec<MarkdownStyle>,
+    /// The end of the last closing delimiter.
+    delimiter_end: Option<usize>
+}
+
+#[derive(Debug, Clone, Copy)]
+struct MarkdownStyle {
+    style: Style,
+    /// The position in the Markdown at which the style's text starts. Opening delimiters and
+    /// elements are only inserted there once the style is popped and its text is known.
+    start: usize,
+    /// Whether any other italic or bold style has been pushed after this one.
+    nested: bool
+}
+
+impl MarkdownWriter {
+    /// Closes all remaining styles and returns the Markdown.
+    pub fn finish(mut self) -> String {
+        while let Some(style) = self.styles.last().map(|x| x.style) {
+            self.pop_style(style);
+        }
+
+        self.markdown
+    }
+
+    fn insert(&mut self, index: usize, s: &str) {
+        self.markdown.insert_str(index, s);
+
+        if let Some(end) = self.delimiter_end.as_mut().filter(|x| index < **x) {
+            *end += s.len();
+        }
+    }
+
+    /// Whether the style's text can be enclosed in delimiters.
+    fn is_unambiguous(&self, style: &MarkdownStyle) -> bool {
+        let text = &self.markdown[style.start..];
+
+        !style.nested
+            && self.delimiter_end != Some(style.start)
+            && text.chars().next().is_some_and(char::is_alphanumeric)
+            && text.chars().next_back().is_some_and(char::is_alphanumeric)
+    }
+}
+
+/// Gets the delimiter, opening element and closing element of an italic or bold style.
+fn markdown_emphasis(style: Style) -> (&'static str, &'static str, &'static str) {
+    match style {
+        Style::Italic => ("*", "<em>", "</em>"),
+        Style::Bold => ("**", "<strong>", "</strong>"),
+        Style::Color(_) | Style::EdgeColor(_) => ("", "", "")
+    }
+}
+
+impl SeStringWriter for MarkdownWriter {
+    fn text(&mut self, text: &str) {
+        for (i, line) in text.split('\n').enumerate() {
+            if i != 0 {
+                self.new_line();
+            }
+
+            self.markdown.push_str(&escape_markdown(line.trim_end_matches('\r')));
+        }
+    }
+
+    fn new_line(&mut self) {
+        self.markdown.push_str("<br>");
+    }
+
+    fn push_style(&mut self, style: Style) {
+        if matches!(style, Style::Italic | Style::Bold) {
+            self.styles.iter_mut().for_each(|x| x.nested = true);
+            self.styles.push(MarkdownStyle { style, start: self.markdown.len(), nested: false });
+        }
+    }
+
+    fn pop_style(&mut self, style: Style) {
+        let Some(index) = self.styles.iter().rposition(|x| x.style.is_same_kind(&style)) else {
+            return;
+        };
+
+        let overlapping = self.styles.split_off(index + 1);
+        let popped = self.styles.remove(index);
+
+        if overlapping.is_empty() && self.is_unambiguous(&popped) {
+            let (delimiter, _, _) = markdown_emphasis(popped.style);
+            self.insert(popped.start, delimiter);
+            self.markdown.push_str(delimiter);
+            self.delimiter_end = Some(self.markdown.len());
+
+            return;
+        }
+
+        // Like in HTML, styles that were pushed after the popped one are closed and reopened.
+        // Their openings are inserted from back to front, so that the positions before them stay valid.
+        for style in overlapping.iter().rev().chain([&popped]) {
+            self.insert(style.start, markdown_emphasis(style.style).1);
+        }
+
+        for style in overlapping.iter().rev().chain([&popped]) {
+            self.markdown.push_str(markdown_emphasis(style.style).2);
+        }
+
+        let start = self.markdown.len();
+        let count = overlapping.len();
+        self.styles.extend(overlapping.into_iter().enumerate().map(|(i, x)| MarkdownStyle { start, nested: i + 1 < count, ..x }));
+    }
+}
+
+/// Escapes all characters that have a meaning within a line of Markdown,
+/// including `|` (which would end a table cell) and `<` (which would start HTML).
+pub(crate) fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '|' | '~') {
+            escaped.push('\\');
+        }
+
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(text: &'static str) -> Expression<'static> {
+        Expression::String(SeString::new(text.as_bytes()))
+    }
+
+    fn global(index: u32) -> Expression<'static> {
+        Expression::GlobalNumber(Box::new(Expression::U32(index)))
+    }
+
+    fn plain(kind: MacroKind, arguments: &[Expression], context: RenderContext) -> String {
+        let mut writer = PlainWriter::default();
+        render_macro(kind, arguments, context, &mut writer).unwrap();
+
+        writer.finish()
+    }
+
+    #[test]
+    fn html_pops_out_of_order() {
+        let mut writer = HtmlWriter::default();
+        writer.push_style(Style::Color(0xff0000));
+        writer.text("a");
+        writer.push_style(Style::Bold);
+        writer.text("b");
+        writer.pop_style(Style::Color(0));
+        writer.text("c");
+
+        assert_eq!(writer.finish(), "<span style=\"color:#ff0000\">a<span style=\"font-weight:bold\">b</span></span><span style=\"font-weight:bold\">c</span>");
+    }
+
+    #[test]
+    fn html_pops_nested_styles_of_same_kind() {
+        let mut writer = HtmlWriter::default();
+        writer.push_style(Style::Color(0x00ff00));
+        writer.push_style(Style::Color(0x0000ff));
+        writer.text("a");
+        writer.pop_style(Style::Color(0));
+        writer.text("b");
+        writer.pop_style(Style::Color(0));
+        // Styles that were never pushed are ignored.
+        writer.pop_style(Style::Italic);
+        writer.text("c");
+
+        assert_eq!(writer.finish(), "<span style=\"color:#00ff00\"><span style=\"color:#0000ff\">a</span>b</span>c");
+    }
+
+    #[test]
+    fn html_escapes_text() {
+        let mut writer = HtmlWriter::default();
+        writer.text("<a href=\"x\">&'</a>\r\nb");
+        writer.non_breaking_space();
+
+        assert_eq!(writer.finish(), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;&lt;/a&gt;<br>b&nbsp;");
+    }
+
+    #[test]
+    fn color_macros_push_and_pop_styles() {
+        let mut writer = HtmlWriter::default();
+        let context = RenderContext::default();
+        render_macro(MacroKind::Color, &[Expression::U32(0xff123456)], context, &mut writer).unwrap();
+        writer.text("a");
+        render_macro(MacroKind::Color, &[Expression::StackColor], context, &mut writer).unwrap();
+        // Without UIColor colors, color types are skipped.
+        render_macro(MacroKind::ColorType, &[Expression::U32(500)], context, &mut writer).unwrap();
+        writer.text("b");
+
+        assert_eq!(writer.finish(), "<span style=\"color:#123456\">a</span>b");
+    }
+
+    #[test]
+    fn if_evaluates_parameters() {
+        let arguments = [Expression::Ge(Box::new(global(GlobalParameters::LEVEL)), Box::new(Expression::U32(50))), string("high"), string("low")];
+        let parameters = GlobalParameters([(GlobalParameters::LEVEL, 90)].into());
+
+        assert_eq!(plain(MacroKind::If, &arguments, RenderContext::default()), "low");
+        assert_eq!(plain(MacroKind::If, &arguments, RenderContext { parameters: Some(&parameters), ..RenderContext::default() }), "high");
+    }
+
+    #[test]
+    fn if_pc_gender_evaluates_gender() {
+        let arguments = [global(GlobalParameters::GENDER), string("he"), string("she")];
+        let parameters = GlobalParameters([(GlobalParameters::GENDER, 1)].into());
+
+        assert_eq!(plain(MacroKind::IfPcGender, &arguments, RenderContext::default()), "he");
+        assert_eq!(plain(MacroKind::IfPcGender, &arguments, RenderContext { parameters: Some(&parameters), ..RenderContext::default() }), "she");
+    }
+
+    #[test]
+    fn switch_cases_start_at_one() {
+        let arguments = [global(GlobalParameters::CLASS_JOB), string("first"), string("second")];
+        let context = |job| GlobalParameters([(GlobalParameters::CLASS_JOB, job)].into());
+
+        assert_eq!(plain(MacroKind::Switch, &arguments, RenderContext::default()), "");
+        assert_eq!(plain(MacroKind::Switch, &arguments, RenderContext { parameters: Some(&context(1)), ..RenderContext::default() }), "first");
+        assert_eq!(plain(MacroKind::Switch, &arguments, RenderContext { parameters: Some(&context(2)), ..RenderContext::default() }), "second");
+        assert_eq!(plain(MacroKind::Switch, &arguments, RenderContext { parameters: Some(&context(3)), ..RenderContext::default() }), "");
+    }
+
+    #[test]
+    fn plain_text_keeps_whitespace() {
+        let mut writer = PlainWriter::default();
+        writer.text("  a\tb\u{1}c\u{ad}d ");
+        writer.non_breaking_space();
+        writer.text("e\n\n\r\nf");
+
+        assert_eq!(writer.finish(), "a bcd e\nf");
+    }
+
+    #[test]
+    fn search_writes_every_branch() {
+        let context = RenderContext { all_branches: true, ..RenderContext::default() };
+
+        assert_eq!(plain(MacroKind::If, &[global(GlobalParameters::LEVEL), string("high"), string("low")], context), "high low");
+        assert_eq!(plain(MacroKind::IfPcGender, &[global(GlobalParameters::GENDER), string("he"), string("she")], context), "he she");
+        assert_eq!(plain(MacroKind::Switch, &[global(GlobalParameters::CLASS_JOB), string("first"), string("second")], context), "first second");
+    }
+
+    #[test]
+    fn markdown_pops_out_of_order() {
+        let mut writer = MarkdownWriter::default();
+        writer.push_style(Style::Italic);
+        writer.text("a");
+        writer.push_style(Style::Bold);
+        writer.text("b");
+        writer.pop_style(Style::Italic);
+        writer.text("c");
+
+        assert_eq!(writer.finish(), "<em>a<strong>b</strong></em>**c**");
+    }
+
+    #[test]
+    fn markdown_avoids_ambiguous_delimiters() {
+        let mut writer = MarkdownWriter::default();
+        writer.push_style(Style::Italic);
+        writer.text("a");
+        writer.pop_style(Style::Italic);
+        writer.push_style(Style::Bold);
+        writer.text("b");
+        writer.pop_style(Style::Bold);
+        writer.push_style(Style::Italic);
+        writer.text("c");
+        writer.push_style(Style::Italic);
+        writer.text("d");
+        writer.pop_style(Style::Italic);
+        writer.pop_style(Style::Italic);
+        writer.push_style(Style::Bold);
+        writer.text("(e)");
+        writer.pop_style(Style::Bold);
+
+        assert_eq!(writer.finish(), "*a*<strong>b</strong><em>c*d*</em><strong>(e)</strong>");
+    }
+
+    #[test]
+    fn markdown_escapes_text() {
+        let mut writer = MarkdownWriter::default();
+        writer.push_style(Style::Color(0xff0000));
+        writer.text("*a* | [b]_`c`\\\r\n<d>");
+        writer.pop_style(Style::Color(0));
+
+        assert_eq!(writer.finish(), "\\*a\\* \\| \\[b\\]\\_\\`c\\`\\\\<br>\\<d\\>");
+    }
+}
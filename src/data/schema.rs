@@ -0,0 +1,138 @@
+use ironworks::file::exh::ColumnKind;
+use serde::Serialize;
+use crate::err::Err;
+use super::format::{Table, TabularResult};
+use super::sheet_extractor::RowExtractor;
+use super::sheets::Sheet;
+use super::{write_json, IronworksCli, WritableResult};
+
+/// Describes every column that is output for rows of a sheet,
+/// as returned by [`IronworksCli::sheet_schema()`].
+#[derive(Debug, Serialize)]
+pub struct SheetSchema(Vec<ColumnSchema>);
+
+/// Describes a single (flattened) column of a sheet.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnSchema {
+    /// The key of the column in JSON output. Nested columns are named
+    /// after their path, e.g. `baseParam[0]`, and so are the columns of
+    /// referenced rows, e.g. `classJob.abbreviation`.
+    pub key: String,
+    /// The sheet that the column is read from. This differs from the requested sheet
+    /// for columns of linked sheets, referenced rows and the names of referenced rows.
+    pub sheet: String,
+    /// The kind of the column as stored in the game files, e.g. `uint32`.
+    pub kind: &'static str,
+    /// The offset of the column within a row of its sheet.
+    pub offset: u16,
+    /// The sheets that the column references according to EXDSchema.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<String>,
+    /// How the column's values are resolved in the output, if at all:
+    /// `row` if they are replaced by the referenced row,
+    /// or `identifier` if the referenced row's name is added next to them, e.g. as `itemUICategoryName`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resolved: Option<&'static str>
+}
+
+/// A result combined with the schema of its sheet.
+/// It is serialized as an object with the keys `schema` and `data`.
+#[derive(Debug, Serialize)]
+pub struct WithSchema<T> {
+    pub schema: SheetSchema,
+    pub data: T
+}
+
+impl IronworksCli {
+    /// Describes every column that [`IronworksCli::get()`] and [`IronworksCli::export()`]
+    /// output for the given sheet, taking the configured link depth into account.
+    pub fn sheet_schema(&self, sheet: &Sheet) -> Result<SheetSchema, Err> {
+        let sheet_info = self.get_sheet(sheet.name())?;
+        let extractor = self.row_extractor(&sheet_info)?;
+        let mut columns = Vec::new();
+        describe_columns(&extractor, "", &mut columns);
+
+        Ok(SheetSchema(columns))
+    }
+}
+
+/// Describes every column of the extractor, prefixing their keys with `prefix`.
+///
+/// Columns whose references are resolved are followed by the columns that they
+/// are resolved to, i.e. the name of the referenced row (e.g. `itemUICategoryName`)
+/// or every column of the referenced row (e.g. `classJob.abbreviation`).
+fn describe_columns(extractor: &RowExtractor, prefix: &str, columns: &mut Vec<ColumnSchema>) {
+    for column in extractor.flat_columns() {
+        let key = format!("{}{}", prefix, column.key);
+
+        columns.push(ColumnSchema {
+            key: key.clone(),
+            sheet: column.sheet.to_owned(),
+            kind: kind_name(column.column.kind()),
+            offset: column.column.offset(),
+            targets: column.references.iter().map(|x| x.sheet.clone()).collect(),
+            resolved: extractor.resolution(&column)
+        });
+
+        if let Some((name_key, sheet, identifier)) = extractor.name_column(&column) {
+            columns.push(ColumnSchema {
+                key: format!("{}{}", prefix, name_key),
+                sheet: sheet.to_owned(),
+                kind: kind_name(identifier.kind()),
+                offset: identifier.offset(),
+                targets: Vec::new(),
+                resolved: None
+            });
+        }
+
+        for referenced in extractor.row_extractors(&column) {
+            describe_columns(referenced, &format!("{}.", key), columns);
+        }
+    }
+}
+
+impl <T: Serialize> WritableResult for WithSchema<T> {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, false)
+    }
+
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+}
+
+/// The schema isn't part of tabular output, since the header already names every column.
+impl <T: TabularResult> TabularResult for WithSchema<T> {
+    fn table(&self) -> Table {
+        self.data.table()
+    }
+
+    fn write_markdown(&self, w: impl std::io::Write, escape: bool) -> std::io::Result<()> {
+        self.data.write_markdown(w, escape)
+    }
+}
+
+fn kind_name(kind: ColumnKind) -> &'static str {
+    match kind {
+        ColumnKind::String => "string",
+        ColumnKind::Bool => "bool",
+        ColumnKind::Int8 => "int8",
+        ColumnKind::UInt8 => "uint8",
+        ColumnKind::Int16 => "int16",
+        ColumnKind::UInt16 => "uint16",
+        ColumnKind::Int32 => "int32",
+        ColumnKind::UInt32 => "uint32",
+        ColumnKind::Float32 => "float32",
+        ColumnKind::Int64 => "int64",
+        ColumnKind::UInt64 => "uint64",
+        ColumnKind::PackedBool0
+            | ColumnKind::PackedBool1
+            | ColumnKind::PackedBool2
+            | ColumnKind::PackedBool3
+            | ColumnKind::PackedBool4
+            | ColumnKind::PackedBool5
+            | ColumnKind::PackedBool6
+            | ColumnKind::PackedBool7 => "bool"
+    }
+}
@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use ironworks::excel::Field;
+use ironworks::sestring::{Expression, Payload, SeString};
+use serde::Serialize;
+use crate::err::Err;
+use super::render::{render, GlobalParameters, HtmlWriter, MarkdownWriter, PlainWriter, RenderContext, TextWriter};
+use super::{Id, IronworksCli, Value};
+
+/// How string fields are written in output.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+#[clap(rename_all = "lowercase")]
+pub enum SeStringFormat {
+    /// The text of the string. Macros are replaced by their default text, if any.
+    #[default]
+    Text,
+    /// A tree of text runs and macros along with their arguments. See [`Token`].
+    Tokens,
+    /// Sanitized HTML, where colors, edge colors, italics and emphasis become `<span>`
+    /// elements with inline styles and line breaks become `<br>`. Color IDs are resolved
+    /// via the UIColor sheet.
+    Html,
+    /// Normalized plain text, e.g. for search indexing. See [`IronworksCli::plain_text()`].
+    Plain,
+    /// Escaped Markdown, where italics and emphasis become `*` and `**` (or `<em>` and `<strong>`
+    /// where delimiters would be ambiguous) and line breaks become `<br>`. Colors are dropped.
+    Markdown
+}
+
+/// The character that SeStrings are evaluated for, as set by [`crate::IronworksBuilder::player_context()`].
+/// Conditional text that depends on unset values is evaluated as if they were `0`.
+#[derive(Debug, Clone, Default)]
+pub struct PlayerContext {
+    pub level: Option<u32>,
+    /// The ID or abbreviation of the class or job, e.g. `PLD`.
+    pub job: Option<Id>,
+    pub gender: Option<Gender>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Gender {
+    Male,
+    Female
+}
+
+/// A part of an SeString, as written with [`SeStringFormat::Tokens`].
+///
+/// It is serialized as an object whose `type` key is either `text` or `macro`,
+/// e.g. `{"type":"macro","kind":"Color","arguments":[{"type":"number","value":500}]}`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Token {
+    /// A run of plain text.
+    Text { text: String },
+    /// A macro, e.g. `NewLine`, `Color` or `If`. Unknown macros are named after their code, e.g. `Unknown(42)`.
+    Macro { kind: String, arguments: Vec<Argument> }
+}
+
+/// An argument of a macro [`Token`].
+///
+/// It is serialized as an object whose `type` key is the name of the variant in camel case.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Argument {
+    Number { value: u32 },
+    /// A nested string, e.g. one of the branches of an `If` macro.
+    String { tokens: Vec<Token> },
+    /// A value that is only known at runtime, e.g. `hour` or `stackColor`.
+    Placeholder { name: &'static str },
+    /// A parameter that is passed to the string at runtime, e.g. the player's level.
+    /// `scope` is `local` or `global` and `kind` is `number` or `string`.
+    Parameter { scope: &'static str, kind: &'static str, index: Box<Argument> },
+    /// A comparison of two arguments, where `operator` is one of `ge`, `gt`, `le`, `lt`, `eq` or `ne`.
+    Comparison { operator: &'static str, left: Box<Argument>, right: Box<Argument> }
+}
+
+impl IronworksCli {
+    /// Formats the SeString of a string field, or returns [`None`] if the field is kept
+    /// as it is (i.e. for [`SeStringFormat::Text`] without a [`PlayerContext`]).
+    ///
+    /// Token trees are never evaluated, since they contain every branch of conditional macros.
+    pub(crate) fn format_sestring(&self, s: &SeString, format: SeStringFormat) -> Result<Option<Value>, Err> {
+        let context = RenderContext { parameters: self.player_parameters.as_ref(), ..RenderContext::default() };
+
+        Ok(match format {
+            SeStringFormat::Text if self.player_parameters.is_none() => None,
+            SeStringFormat::Text => {
+                let mut writer = TextWriter::default();
+                render(s, context, &mut writer)?;
+
+                Some(Value::Formatted(writer.finish()))
+            },
+            SeStringFormat::Tokens => Some(Value::Tokens(tokens(s)?)),
+            SeStringFormat::Html => {
+                let mut writer = HtmlWriter::default();
+                render(s, RenderContext { colors: Some(self.ui_colors()?), ..context }, &mut writer)?;
+
+                Some(Value::Formatted(writer.finish()))
+            },
+            SeStringFormat::Plain => Some(Value::Formatted(self.plain_text(s)?)),
+            SeStringFormat::Markdown => {
+                let mut writer = MarkdownWriter::default();
+                render(s, context, &mut writer)?;
+
+                Some(Value::Formatted(writer.finish()))
+            }
+        })
+    }
+
+    /// Gets the value of a field as it is written in results, i.e. string fields are formatted
+    /// with the configured [`SeStringFormat`] (see [`IronworksCli::format_sestring()`]).
+    pub(crate) fn field_value(&self, field: Field) -> Result<Value, Err> {
+        if let Field::String(s) = &field {
+            if let Some(value) = self.format_sestring(s, self.sestring_format)? {
+                return Ok(value);
+            }
+        }
+
+        Ok(Value::Field(field))
+    }
+
+    /// Gets the normalized plain text of an SeString, as used by [`SeStringFormat::Plain`].
+    /// Conditional text is evaluated like in any other format.
+    ///
+    /// Soft hyphens, control characters and macros without a textual representation (e.g. icons
+    /// and auto-translate phrases) are removed. Non-breaking spaces and other whitespace become
+    /// regular spaces, runs of whitespace are collapsed and lines are trimmed.
+    pub fn plain_text(&self, s: &SeString) -> Result<String, Err> {
+        let mut writer = PlainWriter::default();
+        render(s, RenderContext { parameters: self.player_parameters.as_ref(), ..RenderContext::default() }, &mut writer)?;
+
+        Ok(writer.finish())
+    }
+
+    /// Gets the text that [`IronworksCli::search()`] matches an SeString by. Like [`IronworksCli::plain_text()`],
+    /// but every branch of conditional text is included, so that e.g. text that is only shown
+    /// above a certain level is found regardless of the [`PlayerContext`].
+    pub fn search_text(&self, s: &SeString) -> Result<String, Err> {
+        let mut writer = PlainWriter::default();
+        render(s, RenderContext { all_branches: true, ..RenderContext::default() }, &mut writer)?;
+
+        Ok(writer.finish())
+    }
+
+    /// Gets the values of the global parameters that correspond to the player context.
+    pub(crate) fn global_parameters(&self, context: PlayerContext) -> Result<GlobalParameters, Err> {
+        let mut parameters = HashMap::new();
+
+        if let Some(level) = context.level {
+            parameters.insert(GlobalParameters::LEVEL, level);
+        }
+
+        if let Some(job) = context.job {
+            parameters.insert(GlobalParameters::CLASS_JOB, self.get_job(job)?.id);
+        }
+
+        if let Some(gender) = context.gender {
+            parameters.insert(GlobalParameters::GENDER, match gender {
+                Gender::Male => 0,
+                Gender::Female => 1
+            });
+        }
+
+        Ok(GlobalParameters(parameters))
+    }
+}
+
+/// Splits the SeString into its text runs and macros.
+pub fn tokens(s: &SeString) -> Result<Vec<Token>, Err> {
+    s.payloads()
+        .map(|payload| Ok(match payload.map_err(Err::SeStringError)? {
+            Payload::Text(text) => Token::Text { text: text.as_utf8().map_err(Err::SeStringError)?.to_owned() },
+            Payload::Macro(payload) => Token::Macro {
+                kind: format!("{:?}", payload.kind()),
+                arguments: payload.expressions()
+                    .map(|expression| argument(&expression.map_err(Err::SeStringError)?))
+                    .collect::<Result<_, Err>>()?
+            }
+        }))
+        .collect()
+}
+
+fn argument(expression: &Expression) -> Result<Argument, Err> {
+    let parameter = |scope, kind, index: &Expression| -> Result<Argument, Err> {
+        Ok(Argument::Parameter { scope, kind, index: Box::new(argument(index)?) })
+    };
+    let comparison = |operator, left: &Expression, right: &Expression| -> Result<Argument, Err> {
+        Ok(Argument::Comparison { operator, left: Box::new(argument(left)?), right: Box::new(argument(right)?) })
+    };
+
+    match expression {
+        Expression::U32(value) => Ok(Argument::Number { value: *value }),
+        Expression::String(s) => Ok(Argument::String { tokens: tokens(s)? }),
+        Expression::Millisecond => Ok(Argument::Placeholder { name: "millisecond" }),
+        Expression::Second => Ok(Argument::Placeholder { name: "second" }),
+        Expression::Minute => Ok(Argument::Placeholder { name: "minute" }),
+        Expression::Hour => Ok(Argument::Placeholder { name: "hour" }),
+        Expression::Day => Ok(Argument::Placeholder { name: "day" }),
+        Expression::Weekday => Ok(Argument::Placeholder { name: "weekday" }),
+        Expression::Month => Ok(Argument::Placeholder { name: "month" }),
+        Expression::Year => Ok(Argument::Placeholder { name: "year" }),
+        Expression::StackColor => Ok(Argument::Placeholder { name: "stackColor" }),
+        Expression::LocalNumber(index) => parameter("local", "number", index),
+        Expression::GlobalNumber(index) => parameter("global", "number", index),
+        Expression::LocalString(index) => parameter("local", "string", index),
+        Expression::GlobalString(index) => parameter("global", "string", index),
+        Expression::Ge(left, right) => comparison("ge", left, right),
+        Expression::Gt(left, right) => comparison("gt", left, right),
+        Expression::Le(left, right) => comparison("le", left, right),
+        Expression::Lt(left, right) => comparison("lt", left, right),
+        Expression::Eq(left, right) => comparison("eq", left, right),
+        Expression::Ne(left, right) => comparison("ne", left, right),
+        _ => Ok(Argument::Placeholder { name: "unknown" })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macro_tokens_serialize_arguments() {
+        let level = Expression::GlobalNumber(Box::new(Expression::U32(69)));
+        let expressions = [
+            Expression::Ge(Box::new(level), Box::new(Expression::U32(50))),
+            Expression::String(SeString::new("high".as_bytes())),
+            Expression::StackColor
+        ];
+        let token = Token::Macro {
+            kind: "If".to_owned(),
+            arguments: expressions.iter().map(argument).collect::<Result<_, Err>>().unwrap()
+        };
+
+        assert_eq!(serde_json::to_value(&token).unwrap(), serde_json::json!({
+            "type": "macro",
+            "kind": "If",
+            "arguments": [
+                {
+                    "type": "comparison",
+                    "operator": "ge",
+                    "left": { "type": "parameter", "scope": "global", "kind": "number", "index": { "type": "number", "value": 69 } },
+                    "right": { "type": "number", "value": 50 }
+                },
+                { "type": "string", "tokens": [{ "type": "text", "text": "high" }] },
+                { "type": "placeholder", "name": "stackColor" }
+            ]
+        }));
+    }
+}
@@ -1,101 +1,204 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
-use ironworks::excel::Field;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use indexmap::IndexMap;
+use ironworks::excel::{Field, Language, Row};
 use ironworks::file::exh::ColumnDefinition;
 use ironworks::sestring::SeString;
+use ironworks_schema::ReferenceTarget;
+use serde::ser::SerializeMap;
+use serde::{Serialize, Serializer};
 use crate::err::{Err, ToUnknownErr};
-use super::sheets::{LinkCondition, LinkSource, SHEET_COLUMNS};
-use super::{IronworksCli, WritableResult};
+use super::sheets::{LinkCondition, SheetLink, SHEET_COLUMNS};
+use super::format::{flatten, value_cell, write_definition_list, Table, TabularResult};
+use super::sestring::{SeStringFormat, Token};
+use super::{language_code, serialize_field, write_json, write_json_lines, IronworksCli, Rows, SheetInfo, SheetRow, WritableResult};
 
 impl IronworksCli {
     /// Extracts a single row from the given sheet and prints a
     /// JSON representation of the result to the given output stream.
-    pub fn get(&self, sheet: super::sheets::Sheet, id: u32) -> Result<KeyValues, Err> {
+    ///
+    /// Any sheet can be passed via [`Sheet::Other`](super::sheets::Sheet::Other),
+    /// in which case all of its columns are extracted.
+    ///
+    /// If the sheet is a subrow sheet, all subrows of the row are extracted.
+    /// Use [`IronworksCli::get_subrow()`] to extract a single subrow instead.
+    pub fn get(&self, sheet: super::sheets::Sheet, id: u32) -> Result<RowValues, Err> {
         self.get_values(sheet, id)
     }
 
+    /// Extracts a single subrow from the given subrow sheet.
+    pub fn get_subrow(&self, sheet: super::sheets::Sheet, id: u32, subrow_id: u16) -> Result<KeyValues, Err> {
+        let sheet_name = sheet.name();
+        let sheet_info = self.get_sheet(sheet_name)?;
+        let row = sheet_info.sheet.subrow(id, subrow_id).map_err(|_| Err::SubrowNotFound(sheet_name.to_owned().into(), id, subrow_id))?;
+
+        self.row_extractor(&sheet_info)?.extract(&row)
+    }
+
     /// Gets a [`Vec`] of the field values and their field names
     /// from the given row in the given sheet.
     ///
     /// Note that this function does not extract _all_ fields. Instead only
-    /// the fields specified in `sheets.rs` are extracted.
-    fn get_values(&self, sheet: super::sheets::Sheet, row_id: u32) -> Result<KeyValues, Err> {
-        let sheet_name: &'static str = sheet.into();
+    /// the fields specified in `sheets.rs` are extracted, unless the sheet
+    /// has no entry there.
+    fn get_values(&self, sheet: super::sheets::Sheet, row_id: u32) -> Result<RowValues, Err> {
+        let sheet_name = sheet.name();
         let sheet_info = self.get_sheet(sheet_name)?;
+        let extractor = self.row_extractor(&sheet_info)?;
+
+        if sheet_info.has_subrows()? {
+            let subrows = extractor.extract_subrows(&sheet_info.sheet, row_id)?;
+
+            if subrows.is_empty() {
+                return Err(Err::RowNotFound(sheet_name.to_owned().into(), row_id));
+            }
+
+            return Ok(RowValues::Subrows(subrows));
+        }
+
         // For some reason calling `sheet.row()` on the Action sheet
         // takes longer than any other sheet by a magnitude of about 4x.
         // Since this is a bug in the dependency, we can't fix it.
-        let row = sheet_info.sheet.row(row_id).map_err(|_| Err::RowNotFound(sheet_name, row_id))?;
+        let row = sheet_info.sheet.row(row_id).map_err(|_| Err::RowNotFound(sheet_name.to_owned().into(), row_id))?;
+
+        Ok(RowValues::Row(extractor.extract(&row)?))
+    }
+
+    /// Creates a [`RowExtractor`] for the given sheet that can be reused
+    /// to extract any number of rows from that sheet.
+    pub(crate) fn row_extractor<'a>(&'a self, sheet_info: &SheetInfo) -> Result<RowExtractor<'a>, Err> {
+        self.row_extractor_with_depth(sheet_info, self.link_depth, false, &mut HashMap::new())
+    }
+
+    /// Creates a [`RowExtractor`] that leaves every field as it is stored in the sheet,
+    /// i.e. references aren't resolved and strings are only read in a single language.
+    /// Linked sheets are still included.
+    pub(crate) fn raw_row_extractor<'a>(&'a self, sheet_info: &SheetInfo) -> Result<RowExtractor<'a>, Err> {
+        self.row_extractor_with_depth(sheet_info, 0, true, &mut HashMap::new())
+    }
+
+    /// Creates a [`RowExtractor`] that resolves `depth` levels of references. Since many sheets
+    /// reference the same sheets, the extractors of referenced sheets are shared via `cache`,
+    /// keyed by sheet name and depth.
+    fn row_extractor_with_depth<'a>(&'a self, sheet_info: &SheetInfo, depth: u8, raw: bool, cache: &mut ExtractorCache<'a>) -> Result<RowExtractor<'a>, Err> {
+        let all_languages = self.all_languages && !raw;
+        let sheet_name = sheet_info.name;
         let sheet_data = SHEET_COLUMNS.get(sheet_name);
-        let filtered_columns: Vec<SheetColumn> = if let Some(sheet_data) = sheet_data {
+        let columns: Vec<SheetColumn> = if let Some(sheet_data) = sheet_data {
             sheet_info.filtered_columns(sheet_data.columns)?.collect()
         }  else {
             sheet_info.columns()?.collect()
         };
 
-        let mut result: KeyValues = filtered_columns.iter()
-            .map(|column| (Cow::Owned(column.name.to_owned()), row.field(&column.column).unwrap()))
-            .collect();
+        let mut links = Vec::new();
 
-        if let Some(data) = sheet_data {
-            for link in data.links {
-                if match link.condition {
-                    LinkCondition::Always => false,
-                    LinkCondition::Predicate(condition_col, predicate) => !predicate(result.iter().find(|x| x.0 == condition_col).to_unknown_err(21)?.1)
-                } {
+        for link in sheet_data.map_or(&[] as &[SheetLink], |x| x.links) {
+            let linked_sheet_info = self.get_sheet(link.sheet)?;
+            let linked_columns = linked_sheet_info.columns()?
+                .filter_map(|column| Some((link.columns.iter().find(|x| x.source == column.name)?.target, column.column()?.clone())))
+                .collect();
+
+            let languages = if all_languages { linked_sheet_info.localized_languages()? } else { Vec::new() };
+
+            links.push(LinkedSheet { link, sheet: linked_sheet_info.sheet, columns: linked_columns, languages });
+        }
+
+        let mut references = HashMap::new();
+
+        if depth > 0 {
+            let mut targets = Vec::new();
+            collect_references(&columns, &mut targets);
+
+            for target in targets {
+                if references.contains_key(&target.sheet) {
                     continue;
                 }
 
-                let linked_sheet_info = self.get_sheet(link.sheet)?;
-                let linked_row_id = if let LinkSource::Field(column_name) = link.source {
-                    let value = &result.iter().find(|x| x.0 == column_name).ok_or(Err::ColumnNotFound(sheet_name, column_name))?.1;
-                    get_u32(value).ok_or(Err::NoIndex(sheet_name, column_name))?
-                } else {
-                    row_id
-                };
+                let key = (target.sheet.clone(), depth - 1);
 
-                let linked_row = linked_sheet_info.sheet.row(linked_row_id).map_err(|_| Err::RowNotFound(link.sheet, linked_row_id))?;
+                if let Some(extractor) = cache.get(&key) {
+                    references.insert(target.sheet.clone(), extractor.clone());
+                    continue;
+                }
 
-                for column in linked_sheet_info.columns()? {
-                    let link_data = link.columns.iter().find(|x| x.source == column.name);
+                // EXDSchema occasionally references sheets that don't exist in the game files
+                // (or vice versa). Those references are simply left unresolved.
+                if let Ok(referenced_sheet_info) = self.get_sheet(&target.sheet) {
+                    let extractor = Rc::new(self.row_extractor_with_depth(&referenced_sheet_info, depth - 1, raw, cache)?);
+                    cache.insert(key, extractor.clone());
+                    references.insert(target.sheet.clone(), extractor);
+                }
+            }
+        }
 
-                    if let Some(link_data) = link_data {
-                        result.insert(Cow::Borrowed(link_data.target), linked_row.field(&column.column).to_unknown_err(22)?);
-                    }
+        let resolved_columns = sheet_data.filter(|_| !raw).map_or(&[] as &[&str], |x| x.resolved_columns);
+        let mut identifiers = HashMap::new();
+        let mut targets = Vec::new();
+        collect_references(columns.iter().filter(|x| resolved_columns.contains(&x.name.as_str())), &mut targets);
+
+        for target in targets {
+            if references.contains_key(&target.sheet) || identifiers.contains_key(&target.sheet) {
+                continue;
+            }
+
+            if let Ok(referenced_sheet_info) = self.get_sheet(&target.sheet) {
+                let identifier = [SHEET_COLUMNS.get(target.sheet.as_str()).map_or(DEFAULT_IDENTIFIER, |x| x.identifier)];
+
+                if let Some(column) = referenced_sheet_info.filtered_columns(&identifier)?.next().and_then(|x| x.column().cloned()) {
+                    let languages = if all_languages { referenced_sheet_info.localized_languages()? } else { Vec::new() };
+                    identifiers.insert(target.sheet.clone(), (column, languages));
                 }
             }
         }
 
-        Ok(result)
+        let languages = if all_languages { sheet_info.localized_languages()? } else { Vec::new() };
+        let sestring_format = if raw { SeStringFormat::Text } else { self.sestring_format };
+
+        Ok(RowExtractor { cli: self, sheet_name: sheet_name.to_owned(), columns, links, references, resolved_columns, identifiers, languages, sestring_format })
     }
 
     /// Searches for a given string in the given sheet and prints a list of all matching row IDs
     /// to [`stdout`].
     ///
     /// Note that this function does not search through _all_ columns; instead
-    /// only the columns specified in `sheets.rs` are searched.
+    /// only the columns specified in `sheets.rs` are searched. Sheets that have
+    /// no entry there are searched by their `Name` column.
+    /// Columns are matched case-insensitively by their [search text](IronworksCli::search_text()).
     pub fn search<'a>(&'a self, sheet: super::sheets::Sheet, search_str: &str) -> Result<SearchMatches<'a>, Err> {
-        let sheet_name: &'static str = sheet.into();
+        let sheet_name = sheet.name();
         let sheet_info = self.get_sheet(sheet_name)?;
-        let sheet_data = SHEET_COLUMNS.get(sheet_name).to_unknown_err(23)?;
+        let sheet_data = SHEET_COLUMNS.get(sheet_name);
+        let identifier = sheet_data.map_or(DEFAULT_IDENTIFIER, |x| x.identifier);
+        let search_column_names = sheet_data.map_or(&[DEFAULT_IDENTIFIER] as &[&str], |x| x.search_columns);
     
         let mut matches: SearchMatches<'a> = Vec::new();
-        let filtered_columns: Vec<SheetColumn> = sheet_info.filtered_columns(sheet_data.columns)?.collect();
-        let name_column = filtered_columns.iter().find(|x| x.name == sheet_data.identifier).to_unknown_err(24)?;
-        let search_columns: Vec<_> = filtered_columns.iter().filter(|x| sheet_data.search_columns.contains(&x.name.as_ref())).collect();
+        let filtered_columns: Vec<SheetColumn> = if let Some(sheet_data) = sheet_data {
+            sheet_info.filtered_columns(sheet_data.columns)?.collect()
+        } else {
+            sheet_info.filtered_columns(search_column_names)?.collect()
+        };
+        let name_column = filtered_columns.iter().find(|x| x.name == identifier).and_then(SheetColumn::column).ok_or_else(|| Err::ColumnNotFound(sheet_name.to_owned().into(), identifier))?;
+        let search_columns: Vec<_> = filtered_columns.iter()
+            .filter(|x| search_column_names.contains(&x.name.as_ref()))
+            .filter_map(|x| Some((x.name.as_str(), x.column()?)))
+            .collect();
     
         for row in sheet_info.sheet.into_iter() {
-            let name = row.field(&name_column.column).to_unknown_err(26)?.into_string().to_unknown_err(25)?;
+            let name = row.field(name_column).to_unknown_err(26)?;
     
-            for column in search_columns.iter() {
-                let field = row.field(&column.column).to_unknown_err(27)?;
+            for (column_name, column) in search_columns.iter() {
+                let field = row.field(column).to_unknown_err(27)?;
                 let sestring = field.as_string().to_unknown_err(28)?;
     
-                if sestring.to_string().to_lowercase().contains(&search_str.to_lowercase()) {
-                    if column.name == name_column.name {
-                        matches.push(SearchMatch { id: row.row_id(), name, field: None });
+                if self.search_text(sestring)?.to_lowercase().contains(&search_str.to_lowercase()) {
+                    let name = self.field_value(name)?;
+
+                    if *column_name == identifier {
+                        matches.push(SearchMatch { id: row.row_id(), name, identifier, field: None });
                     } else {
-                        matches.push(SearchMatch { id: row.row_id(), name, field: Some(KeyValue { key: Cow::Owned(column.name.clone()), value: field }) });
+                        matches.push(SearchMatch { id: row.row_id(), name, identifier, field: Some(KeyValue { key: Cow::Owned(column_name.to_string()), value: self.field_value(field)? }) });
                     }
     
                     break;
@@ -107,63 +210,241 @@ impl IronworksCli {
     }
 }
 
+/// Extractors of referenced sheets, keyed by sheet name and link depth.
+type ExtractorCache<'a> = HashMap<(String, u8), Rc<RowExtractor<'a>>>;
+
+/// The column used to identify and search rows in sheets that have no entry in `sheets.rs`.
+const DEFAULT_IDENTIFIER: &str = "Name";
+
 /// The return type of the [`IronworksCli::extract`] function
 /// that can also be written to an [`std::io::Write`] stream.
 ///
-/// Internally it's simply a key value hash map, similar to a JSON object.
-pub type KeyValues<'a> = HashMap<Cow<'a, str>, Field>;
+/// Internally it's simply a key value map, similar to a JSON object.
+/// Keys are kept in the order of the sheet's columns, unless they have
+/// been sorted via [`KeyValues::sort_keys()`].
+#[derive(Debug, Default)]
+pub struct KeyValues<'a>(IndexMap<Cow<'a, str>, Value>);
 
-impl <'a> WritableResult for KeyValues<'a> {
-    fn write(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
-        write!(w, "{{")?;
-        let len = self.len();
-    
-        for (i, (key, field)) in self.iter().enumerate() {
-            write!(w, "\"{}{}\":", &key.chars().next().unwrap().to_lowercase(), &key[1..])?;
-            field.write(&mut w)?;
-    
-            if i < len - 1 {
-                write!(w, ",")?;
-            }
+impl <'a> Deref for KeyValues<'a> {
+    type Target = IndexMap<Cow<'a, str>, Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl <'a> DerefMut for KeyValues<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl <'a> FromIterator<(Cow<'a, str>, Value)> for KeyValues<'a> {
+    fn from_iter<T: IntoIterator<Item = (Cow<'a, str>, Value)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl <'a> KeyValues<'a> {
+    /// Sorts the keys alphabetically (by their JSON representation),
+    /// including the keys of all nested values.
+    pub fn sort_keys(&mut self) {
+        self.0.sort_by(|a, _, b, _| json_key(a).cmp(&json_key(b)));
+
+        for value in self.0.values_mut() {
+            value.sort_keys();
         }
-        writeln!(w, "}}")?;
-    
-        Ok(())
     }
 
-    fn pretty_write(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
-        writeln!(w, "{{")?;
-        let len = self.len();
-    
-        for (i, (key, value)) in self.iter().enumerate() {
-            write!(w, "  \"{}{}\": ", &key.chars().next().unwrap().to_lowercase(), &key[1..])?;
-            value.pretty_write(&mut w)?;
-    
-            if i < len - 1 {
-                writeln!(w, ",")?;
-            }
+    /// Replaces all string fields by the result of `f`, including those of nested values.
+    fn format_strings(&mut self, f: &impl Fn(&SeString) -> Result<Option<Value>, Err>) -> Result<(), Err> {
+        self.0.values_mut().try_for_each(|x| x.format_strings(f))
+    }
+}
+
+impl <'a> Serialize for KeyValues<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.len()))?;
+
+        for (key, value) in self.iter() {
+            map.serialize_entry(&json_key(key), value)?;
         }
-        writeln!(w, "\n}}")?;
-    
+
+        map.end()
+    }
+}
+
+/// The return type of the [`IronworksCli::get()`] function.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum RowValues<'a> {
+    /// The values of a row in a sheet without subrows.
+    Row(KeyValues<'a>),
+    /// The values of every subrow of a row in a subrow sheet.
+    Subrows(Rows<'a>)
+}
+
+impl <'a> WritableResult for RowValues<'a> {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, false)
+    }
+
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+
+    fn write_lines(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        match self {
+            RowValues::Row(values) => values.write_lines(w),
+            RowValues::Subrows(subrows) => subrows.write_lines(w)
+        }
+    }
+}
+
+impl <'a> TabularResult for RowValues<'a> {
+    fn table(&self) -> Table {
+        match self {
+            RowValues::Row(values) => values.table(),
+            RowValues::Subrows(subrows) => subrows.table()
+        }
+    }
+
+    fn write_markdown(&self, w: impl std::io::Write, escape: bool) -> std::io::Result<()> {
+        match self {
+            RowValues::Row(values) => values.write_markdown(w, escape),
+            RowValues::Subrows(subrows) => subrows.write_markdown(w, escape)
+        }
+    }
+}
+
+/// The value of a single column in [`KeyValues`].
+#[derive(Debug)]
+pub enum Value {
+    /// The field as it is stored in the sheet.
+    Field(Field),
+    /// The row of another sheet that the field references.
+    /// See [`crate::IronworksBuilder::link_depth()`].
+    Row(KeyValues<'static>),
+    /// The values of an array of columns.
+    Array(Vec<Value>),
+    /// The values of a group of named columns.
+    Struct(KeyValues<'static>),
+    /// The values of a string field in each language.
+    /// See [`crate::IronworksBuilder::all_languages()`].
+    Localized(Vec<(Language, Value)>),
+    /// A string field split into its text runs and macros.
+    /// See [`crate::IronworksBuilder::sestring_format()`].
+    Tokens(Vec<Token>),
+    /// A string field rendered as text in another format, e.g. HTML.
+    /// See [`crate::IronworksBuilder::sestring_format()`].
+    Formatted(String),
+    /// The absence of a value, e.g. the name of a row that a field doesn't reference.
+    Null
+}
+
+impl Value {
+    /// Gets the contained [`Field`] if this value hasn't been resolved to another row.
+    pub fn as_field(&self) -> Option<&Field> {
+        match self {
+            Value::Field(field) => Some(field),
+            _ => None
+        }
+    }
+
+    fn sort_keys(&mut self) {
+        match self {
+            Value::Row(values) | Value::Struct(values) => values.sort_keys(),
+            Value::Array(elements) => elements.iter_mut().for_each(Value::sort_keys),
+            Value::Field(_) | Value::Localized(_) | Value::Tokens(_) | Value::Formatted(_) | Value::Null => ()
+        }
+    }
+
+    /// Replaces all string fields within this value by the result of `f`, unless it returns [`None`].
+    fn format_strings(&mut self, f: &impl Fn(&SeString) -> Result<Option<Value>, Err>) -> Result<(), Err> {
+        match self {
+            Value::Field(Field::String(s)) => if let Some(formatted) = f(s)? {
+                *self = formatted;
+            },
+            Value::Row(values) | Value::Struct(values) => values.format_strings(f)?,
+            Value::Array(elements) => elements.iter_mut().try_for_each(|x| x.format_strings(f))?,
+            Value::Localized(values) => values.iter_mut().try_for_each(|(_, x)| x.format_strings(f))?,
+            Value::Field(_) | Value::Tokens(_) | Value::Formatted(_) | Value::Null => ()
+        }
+
         Ok(())
     }
 }
 
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Field(field) => serialize_field(field, serializer),
+            Value::Row(values) | Value::Struct(values) => values.serialize(serializer),
+            Value::Array(elements) => elements.serialize(serializer),
+            Value::Localized(values) => {
+                let mut map = serializer.serialize_map(Some(values.len()))?;
+
+                for (language, value) in values.iter() {
+                    map.serialize_entry(language_code(*language), value)?;
+                }
+
+                map.end()
+            },
+            Value::Tokens(tokens) => tokens.serialize(serializer),
+            Value::Formatted(s) => serializer.serialize_str(s),
+            Value::Null => serializer.serialize_none()
+        }
+    }
+}
+
+impl <'a> WritableResult for KeyValues<'a> {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, false)
+    }
+
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+}
+
+impl <'a> TabularResult for KeyValues<'a> {
+    fn table(&self) -> Table {
+        let mut cells = IndexMap::new();
+        flatten(self, "", &mut cells);
+
+        Table::from_rows([cells])
+    }
+
+    /// A single row is written as a definition list instead of a table with a single record.
+    fn write_markdown(&self, w: impl std::io::Write, escape: bool) -> std::io::Result<()> {
+        let mut cells = IndexMap::new();
+        flatten(self, "", &mut cells);
+
+        write_definition_list(&cells, escape, w)
+    }
+}
+
 /// A key value pair for an Excel field.
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct KeyValue<'a> {
     pub key: Cow<'a, str>,
-    pub value: Field
+    pub value: Value
 }
 
 /// A search match that contains references to data that lives
 /// within [`IronworksCli`].
+///
+/// It is serialized as an object with the keys `id`, `name`, `column` and `value`,
+/// where `column` and `value` are the identifier column and the name if only the row's name matched.
 #[derive(Debug)]
 pub struct SearchMatch<'a> {
     /// The ID (or row index) of the found entity.
     pub id: u32,
-    /// The name of the entity as retrieved from its "name" column.
-    pub name: SeString<'a>,
+    /// The name of the entity as retrieved from its "name" column,
+    /// formatted like any other string field.
+    pub name: Value,
+    /// The name of the column that `name` was retrieved from, e.g. `Name`.
+    pub identifier: &'static str,
     /// A key-value pair of the column that matched the search query.
     /// This will be [`None`] if only the row's name matched.
     pub field: Option<KeyValue<'a>>
@@ -172,24 +453,52 @@ pub struct SearchMatch<'a> {
 /// The matches of the [`IronworksCli::search()`] function.
 pub type SearchMatches<'a> = Vec<SearchMatch<'a>>;
 
+impl <'a> Serialize for SearchMatch<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(4))?;
+        map.serialize_entry("id", &self.id)?;
+        map.serialize_entry("name", &self.name)?;
+
+        if let Some(field) = &self.field {
+            map.serialize_entry("column", &json_key(&field.key))?;
+            map.serialize_entry("value", &field.value)?;
+        } else {
+            map.serialize_entry("column", &json_key(self.identifier))?;
+            map.serialize_entry("value", &self.name)?;
+        }
+
+        map.end()
+    }
+}
+
 impl <'a> WritableResult for SearchMatches<'a> {
     fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
-        self.pretty_write(w)
+        write_json(self, w, false)
     }
 
-    fn pretty_write(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+
+    fn write_lines(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json_lines(self.iter(), w)
+    }
+
+    fn write_text(&self, mut w: impl std::io::Write) -> std::io::Result<()> {
         if self.is_empty() {
             writeln!(w, "No matches found")?;
         } else {
             writeln!(w, "{} matches found:", self.len())?;
     
-            for SearchMatch { id, name, field } in self.iter() {
+            for SearchMatch { id, name, field, .. } in self.iter() {
                 write!(w, "  at {: >5}: ", id)?;
-                name.pretty_write(&mut w)?;
+                serde_json::to_writer(&mut w, name)?;
     
                 if let Some(key_value) = field {
-                    write!(w, " -> {{ \"{}\": ", key_value.key)?;
-                    key_value.value.pretty_write(&mut w)?;
+                    write!(w, " -> {{ ")?;
+                    serde_json::to_writer(&mut w, &key_value.key)?;
+                    write!(w, ": ")?;
+                    serde_json::to_writer(&mut w, &key_value.value)?;
                     write!(w, " }}")?;
                 }
     
@@ -201,13 +510,493 @@ impl <'a> WritableResult for SearchMatches<'a> {
     }
 }
 
-pub(crate) struct SheetColumn {
+impl <'a> SearchMatch<'a> {
+    const HEADER: [&'static str; 4] = ["Id", "Name", "Column", "Value"];
+
+    fn record(&self) -> Vec<String> {
+        let (column, value) = self.field.as_ref().map_or((self.identifier.to_owned(), value_cell(&self.name)), |x| (x.key.to_string(), value_cell(&x.value)));
+
+        vec![self.id.to_string(), value_cell(&self.name), column, value]
+    }
+}
+
+/// A single match, e.g. as written to its own file with `--out <dir>`.
+impl <'a> WritableResult for SearchMatch<'a> {
+    fn write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, false)
+    }
+
+    fn pretty_write(&self, w: impl std::io::Write) -> std::io::Result<()> {
+        write_json(self, w, true)
+    }
+}
+
+impl <'a> TabularResult for SearchMatch<'a> {
+    fn table(&self) -> Table {
+        let mut table = Table::new(SearchMatch::HEADER.map(String::from).to_vec());
+        table.push(self.record());
+
+        table
+    }
+}
+
+impl <'a> TabularResult for SearchMatches<'a> {
+    fn table(&self) -> Table {
+        let mut table = Table::new(SearchMatch::HEADER.map(String::from).to_vec());
+        self.iter().for_each(|x| table.push(x.record()));
+
+        table
+    }
+}
+
+pub(crate) struct SheetColumn<C = ColumnDefinition> {
     pub name: String,
-    pub column: ColumnDefinition
+    pub node: ColumnNode<C>
+}
+
+/// The columns covered by a node in a sheet's schema. Columns are only
+/// represented by something other than a [`ColumnDefinition`] in tests.
+pub(crate) enum ColumnNode<C = ColumnDefinition> {
+    /// A single column, along with the sheets that it references according to EXDSchema (if any).
+    Scalar(C, Vec<ReferenceTarget>),
+    /// A fixed number of consecutive nodes of the same shape.
+    Array(Vec<ColumnNode<C>>),
+    /// A group of named nodes.
+    Struct(Vec<SheetColumn<C>>)
+}
+
+impl SheetColumn {
+    /// Gets the column definition if this column is a single scalar column.
+    pub fn column(&self) -> Option<&ColumnDefinition> {
+        match &self.node {
+            ColumnNode::Scalar(column, _) => Some(column),
+            _ => None
+        }
+    }
+}
+
+impl ColumnNode {
+    /// Reads the value of the columns covered by this node from the given row.
+    fn extract(&self, row: &Row) -> Result<Value, Err> {
+        Ok(match self {
+            ColumnNode::Scalar(column, _) => Value::Field(row.field(column).to_unknown_err(31)?),
+            ColumnNode::Array(nodes) => Value::Array(nodes.iter().map(|x| x.extract(row)).collect::<Result<_, Err>>()?),
+            ColumnNode::Struct(columns) => Value::Struct(extract_columns(columns, row)?)
+        })
+    }
+}
+
+fn extract_columns(columns: &[SheetColumn], row: &Row) -> Result<KeyValues<'static>, Err> {
+    columns.iter()
+        .map(|column| Ok((Cow::Owned(column.name.to_owned()), column.node.extract(row)?)))
+        .collect()
+}
+
+/// Reads the given row in each of the given languages.
+/// Languages that the row can't be read in are skipped.
+fn localized_rows(sheet: &ironworks::excel::Sheet<&str>, languages: &[Language], row_id: u32, subrow_id: u16) -> Vec<(Language, Row)> {
+    languages.iter()
+        .filter_map(|language| Some((*language, sheet.with().language(*language).subrow(row_id, subrow_id).ok()?)))
+        .collect()
+}
+
+/// Replaces all string fields covered by the node with their values in every language.
+fn localize(node: &ColumnNode, value: &mut Value, localized_rows: &[(Language, Row)]) -> Result<(), Err> {
+    match (node, &mut *value) {
+        (ColumnNode::Scalar(column, _), Value::Field(Field::String(_))) => *value = localized_field(column, localized_rows)?,
+        (ColumnNode::Array(nodes), Value::Array(elements)) => {
+            for (node, element) in nodes.iter().zip(elements.iter_mut()) {
+                localize(node, element, localized_rows)?;
+            }
+        },
+        (ColumnNode::Struct(columns), Value::Struct(nested)) => {
+            for column in columns {
+                if let Some(value) = nested.get_mut(column.name.as_str()) {
+                    localize(&column.node, value, localized_rows)?;
+                }
+            }
+        },
+        _ => ()
+    }
+
+    Ok(())
+}
+
+/// Wraps the field in a [`Value`], replacing it with its values in every language
+/// if it is a string and `localized_rows` isn't empty.
+fn localized_value(field: Field, column: &ColumnDefinition, localized_rows: &[(Language, Row)]) -> Result<Value, Err> {
+    match field {
+        Field::String(_) if !localized_rows.is_empty() => localized_field(column, localized_rows),
+        field => Ok(Value::Field(field))
+    }
+}
+
+fn localized_field(column: &ColumnDefinition, localized_rows: &[(Language, Row)]) -> Result<Value, Err> {
+    Ok(Value::Localized(localized_rows.iter()
+        .map(|(language, row)| Ok((*language, Value::Field(row.field(column).to_unknown_err(32)?))))
+        .collect::<Result<_, Err>>()?))
+}
+
+/// Collects the reference targets of all (possibly nested) columns.
+fn collect_references<'a>(columns: impl IntoIterator<Item = &'a SheetColumn>, targets: &mut Vec<&'a ReferenceTarget>) {
+    fn collect_node<'a>(node: &'a ColumnNode, targets: &mut Vec<&'a ReferenceTarget>) {
+        match node {
+            ColumnNode::Scalar(_, references) => targets.extend(references.iter()),
+            ColumnNode::Array(nodes) => nodes.iter().for_each(|x| collect_node(x, targets)),
+            ColumnNode::Struct(columns) => collect_references(columns, targets)
+        }
+    }
+
+    for column in columns {
+        collect_node(&column.node, targets);
+    }
+}
+
+/// A single column of extracted rows, after nested columns have been flattened.
+pub(crate) struct FlatColumn<'a> {
+    /// The flattened name of the column, e.g. `BaseParam[0]`.
+    pub name: String,
+    /// The flattened name of the column as it appears in JSON output, e.g. `baseParam[0]`.
+    pub key: String,
+    /// The sheet that the column is read from. This differs from the extracted sheet for linked columns.
+    pub sheet: &'a str,
+    /// The name of the top-level column that this column is nested in (or the column itself).
+    pub root: &'a str,
+    pub column: &'a ColumnDefinition,
+    /// The sheets that the column references according to EXDSchema (if any).
+    pub references: &'a [ReferenceTarget]
+}
+
+/// A sheet that is linked to from another sheet via a [`SheetLink`],
+/// with the columns to be copied over already resolved.
+struct LinkedSheet<'a> {
+    link: &'static SheetLink,
+    sheet: ironworks::excel::Sheet<&'a str>,
+    /// The output name of each linked column, paired with the column itself.
+    columns: Vec<(&'static str, ColumnDefinition)>,
+    /// The languages that string columns are read in. Empty unless all languages are read.
+    languages: Vec<Language>
+}
+
+/// Extracts the columns configured in `sheets.rs` (and the columns of all linked
+/// sheets) from rows of a single sheet.
+///
+/// Creating a [`RowExtractor`] requires reading the sheet's schema, so it should
+/// be reused when extracting more than one row from the same sheet.
+pub(crate) struct RowExtractor<'a> {
+    cli: &'a IronworksCli,
+    sheet_name: String,
+    columns: Vec<SheetColumn>,
+    links: Vec<LinkedSheet<'static>>,
+    /// Extractors for every sheet referenced by [`RowExtractor::columns`], keyed by sheet name.
+    /// This is empty if the maximum link depth has been reached.
+    references: HashMap<String, Rc<RowExtractor<'a>>>,
+    /// Columns that get the identifier of the referenced row added next to them.
+    resolved_columns: &'static [&'static str],
+    /// The identifier column (and localized languages) of every sheet referenced by
+    /// [`RowExtractor::resolved_columns`], keyed by sheet name.
+    identifiers: HashMap<String, (ColumnDefinition, Vec<Language>)>,
+    /// The languages that string columns are read in. Empty unless all languages are read.
+    languages: Vec<Language>,
+    /// The format that string fields are extracted in. Always [`SeStringFormat::Text`] for raw extractors.
+    sestring_format: SeStringFormat
+}
+
+impl <'a> RowExtractor<'a> {
+    /// Extracts the field values and their field names from the given row.
+    pub fn extract(&self, row: &Row) -> Result<KeyValues<'static>, Err> {
+        let mut result = extract_columns(&self.columns, row)?;
+
+        if !self.languages.is_empty() {
+            let sheet = self.cli.excel_sheet(&self.sheet_name)?;
+            let localized_rows = localized_rows(&sheet, &self.languages, row.row_id(), row.subrow_id());
+
+            for column in self.columns.iter() {
+                if let Some(value) = result.get_mut(column.name.as_str()) {
+                    localize(&column.node, value, &localized_rows)?;
+                }
+            }
+        }
+
+        for LinkedSheet { link, sheet, columns, languages } in self.links.iter() {
+            if match link.condition {
+                LinkCondition::Always => false,
+                LinkCondition::Predicate(condition_col, predicate) => !predicate(result.get(condition_col).and_then(Value::as_field).to_unknown_err(21)?)
+            } {
+                continue;
+            }
+
+            let linked_row = sheet.row(row.row_id()).map_err(|_| Err::RowNotFound(link.sheet.into(), row.row_id()))?;
+
+            let localized_rows = localized_rows(sheet, languages, row.row_id(), 0);
+
+            for (target, column) in columns.iter() {
+                result.insert(Cow::Borrowed(*target), localized_value(linked_row.field(column).to_unknown_err(22)?, column, &localized_rows)?);
+            }
+        }
+
+        if !self.identifiers.is_empty() {
+            self.insert_names(&mut result)?;
+        }
+
+        if !self.references.is_empty() {
+            self.resolve_references(&self.columns, &mut result)?;
+        }
+
+        if self.sestring_format != SeStringFormat::Text {
+            result.format_strings(&|s| self.cli.format_sestring(s, self.sestring_format))?;
+        }
+
+        if self.cli.sort_keys {
+            result.sort_keys();
+        }
+
+        Ok(result)
+    }
+
+    /// Gets every column of the extracted rows, with nested columns flattened
+    /// and named like in [`super::format::flatten()`], followed by the columns of linked sheets.
+    pub fn flat_columns(&self) -> Vec<FlatColumn<'_>> {
+        fn flatten_node<'a>(node: &'a ColumnNode, path: (String, String), root: &'a SheetColumn, sheet: &'a str, columns: &mut Vec<FlatColumn<'a>>) {
+            let (name, key) = path;
+
+            match node {
+                ColumnNode::Scalar(column, references) => columns.push(FlatColumn { name, key, sheet, root: &root.name, column, references }),
+                ColumnNode::Array(nodes) => nodes.iter().enumerate()
+                    .for_each(|(i, x)| flatten_node(x, (format!("{}[{}]", name, i), format!("{}[{}]", key, i)), root, sheet, columns)),
+                ColumnNode::Struct(fields) => fields.iter()
+                    .for_each(|x| flatten_node(&x.node, (format!("{}.{}", name, x.name), format!("{}.{}", key, json_key(&x.name))), root, sheet, columns))
+            }
+        }
+
+        let mut columns = Vec::new();
+
+        for column in self.columns.iter() {
+            flatten_node(&column.node, (column.name.clone(), json_key(&column.name)), column, &self.sheet_name, &mut columns);
+        }
+
+        for linked_sheet in self.links.iter() {
+            for (target, column) in linked_sheet.columns.iter() {
+                columns.push(FlatColumn { name: target.to_string(), key: json_key(target), sheet: linked_sheet.link.sheet, root: *target, column, references: &[] });
+            }
+        }
+
+        columns
+    }
+
+    /// Gets how the values of the column are resolved, if they are:
+    /// `"row"` if they are replaced by the referenced row (see [`crate::IronworksBuilder::link_depth()`]),
+    /// or `"identifier"` if the identifier of the referenced row is added next to them.
+    pub(crate) fn resolution(&self, column: &FlatColumn) -> Option<&'static str> {
+        column.references.iter()
+            .filter(|x| x.selector.is_none())
+            .find_map(|target| {
+                if self.references.contains_key(&target.sheet) {
+                    Some("row")
+                } else if self.resolved_columns.contains(&column.root) && self.identifiers.contains_key(&target.sheet) {
+                    Some("identifier")
+                } else {
+                    None
+                }
+            })
+    }
+
+    /// Gets the column that the identifiers of the rows referenced by the column are added as
+    /// (see [`RowExtractor::insert_names()`]), if they are: its JSON key (e.g. `baseParamName[0]`
+    /// for `baseParam[0]`), along with the referenced sheet and its identifier column.
+    pub(crate) fn name_column<'s>(&'s self, column: &FlatColumn) -> Option<(String, &'s str, &'s ColumnDefinition)> {
+        if !self.resolved_columns.contains(&column.root) {
+            return None;
+        }
+
+        let (sheet, (identifier, _)) = column.references.iter()
+            .filter(|x| x.selector.is_none())
+            .find_map(|x| self.identifiers.get_key_value(&x.sheet))?;
+        let root_key = json_key(column.root);
+        let key = format!("{}Name{}", root_key, column.key.strip_prefix(&root_key)?);
+
+        Some((key, sheet.as_str(), identifier))
+    }
+
+    /// Gets the extractors of every sheet whose rows replace the values of the column
+    /// (see [`crate::IronworksBuilder::link_depth()`]).
+    pub(crate) fn row_extractors(&self, column: &FlatColumn) -> Vec<&RowExtractor<'a>> {
+        let mut extractors: Vec<&RowExtractor<'a>> = Vec::new();
+
+        for target in column.references.iter().filter(|x| x.selector.is_none()) {
+            if let Some(extractor) = self.references.get(&target.sheet) {
+                if !extractors.iter().any(|x| x.sheet_name == extractor.sheet_name) {
+                    extractors.push(extractor);
+                }
+            }
+        }
+
+        extractors
+    }
+
+    /// Gets the names of all sheets that are linked to via a [`SheetLink`].
+    pub fn linked_sheets(&self) -> impl Iterator<Item = &'static str> + use<'_> {
+        self.links.iter().map(|x| x.link.sheet)
+    }
+
+    /// Extracts all subrows of the given row. Returns an empty [`Vec`] if the row doesn't exist.
+    pub fn extract_subrows(&self, sheet: &ironworks::excel::Sheet<&str>, row_id: u32) -> Result<Rows<'static>, Err> {
+        let mut subrows = Vec::new();
+
+        while let Ok(subrow) = sheet.subrow(row_id, subrows.len() as u16) {
+            subrows.push(SheetRow { id: row_id, subrow_id: Some(subrow.subrow_id()), values: self.extract(&subrow)? });
+        }
+
+        Ok(subrows)
+    }
+
+    /// Adds the identifiers of the rows referenced by [`RowExtractor::resolved_columns`] next to
+    /// the columns themselves, e.g. `ItemUICategoryName` after `ItemUICategory`. Arrays of references
+    /// get an array of identifiers. The IDs themselves are kept, and references that can't be
+    /// resolved get an empty identifier, so that every row has the same keys.
+    fn insert_names(&self, values: &mut KeyValues<'static>) -> Result<(), Err> {
+        let scope = reference_scope(values);
+        let mut names = Vec::new();
+
+        for column in self.columns.iter().filter(|x| self.resolved_columns.contains(&x.name.as_str())) {
+            if let Some(value) = values.get(column.name.as_str()) {
+                if let Some(name) = self.resolve_names(&column.node, value, &scope)? {
+                    names.push((column.name.as_str(), name));
+                }
+            }
+        }
+
+        for (column, name) in names {
+            let index = values.get_index_of(column).to_unknown_err(48)?;
+            values.shift_insert(index + 1, Cow::Owned(format!("{}Name", column)), name);
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the identifiers of the rows referenced by the value. Returns [`None`] if none of
+    /// the referenced sheets has an identifier, e.g. because they are resolved to entire rows instead.
+    fn resolve_names(&self, node: &ColumnNode, value: &Value, scope: &HashMap<String, u32>) -> Result<Option<Value>, Err> {
+        Ok(match (node, value) {
+            (ColumnNode::Scalar(_, references), Value::Field(field)) => {
+                if !references.iter().any(|x| self.identifiers.contains_key(&x.sheet)) {
+                    return Ok(None);
+                }
+
+                Some(self.resolve_name(field, references, scope)?.unwrap_or(Value::Null))
+            },
+            (ColumnNode::Array(nodes), Value::Array(elements)) => nodes.iter().zip(elements.iter())
+                .map(|(node, element)| self.resolve_names(node, element, scope))
+                .collect::<Result<Vec<_>, Err>>()?
+                .into_iter()
+                .collect::<Option<Vec<_>>>()
+                .map(Value::Array),
+            _ => None
+        })
+    }
+
+    /// Resolves the identifier of the row referenced by a field, trying each of the reference
+    /// targets in order. Returns [`None`] if none of the targets match.
+    fn resolve_name(&self, field: &Field, references: &[ReferenceTarget], scope: &HashMap<String, u32>) -> Result<Option<Value>, Err> {
+        let Some(id) = get_u32(field) else {
+            return Ok(None);
+        };
+
+        for target in matching_targets(references, scope) {
+            if let Some((identifier, languages)) = self.identifiers.get(&target.sheet) {
+                let sheet = self.cli.excel_sheet(&target.sheet)?;
+
+                if let Ok(row) = sheet.row(id) {
+                    let localized_rows = localized_rows(&sheet, languages, id, 0);
+
+                    return localized_value(row.field(identifier).to_unknown_err(33)?, identifier, &localized_rows).map(Some);
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Replaces the values of all referencing columns with the rows they reference.
+    fn resolve_references(&self, columns: &[SheetColumn], values: &mut KeyValues<'static>) -> Result<(), Err> {
+        let scope = reference_scope(values);
+
+        for column in columns {
+            if let Some(value) = values.get_mut(column.name.as_str()) {
+                self.resolve_value(&column.node, value, &scope)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn resolve_value(&self, node: &ColumnNode, value: &mut Value, scope: &HashMap<String, u32>) -> Result<(), Err> {
+        match (node, &mut *value) {
+            (ColumnNode::Scalar(_, references), Value::Field(field)) => {
+                if let Some(resolved) = self.resolve(field, references, scope)? {
+                    *value = resolved;
+                }
+            },
+            (ColumnNode::Array(nodes), Value::Array(elements)) => {
+                for (node, element) in nodes.iter().zip(elements.iter_mut()) {
+                    self.resolve_value(node, element, scope)?;
+                }
+            },
+            (ColumnNode::Struct(columns), Value::Struct(nested)) => self.resolve_references(columns, nested)?,
+            _ => ()
+        }
+
+        Ok(())
+    }
+
+    /// Resolves the row referenced by a field, trying each of the reference targets
+    /// in order. Returns [`None`] if none of the targets match.
+    fn resolve(&self, field: &Field, references: &[ReferenceTarget], scope: &HashMap<String, u32>) -> Result<Option<Value>, Err> {
+        let Some(id) = get_u32(field) else {
+            return Ok(None);
+        };
+
+        for target in matching_targets(references, scope) {
+            if let Some(extractor) = self.references.get(&target.sheet) {
+                if let Ok(row) = self.cli.excel_sheet(&target.sheet)?.row(id) {
+                    return extractor.extract(&row).map(|x| Some(Value::Row(x)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Gets the numeric fields that reference conditions are evaluated against, keyed by column name.
+/// This has to be called before any of the fields are resolved.
+fn reference_scope(values: &KeyValues) -> HashMap<String, u32> {
+    values.iter()
+        .filter_map(|(key, value)| Some((key.to_string(), value.as_field().and_then(get_u32)?)))
+        .collect()
+}
+
+/// Gets the reference targets whose conditions are met by the sibling fields in `scope`.
+/// Targets that select a column other than the row ID are not supported.
+fn matching_targets<'r>(references: &'r [ReferenceTarget], scope: &HashMap<String, u32>) -> impl Iterator<Item = &'r ReferenceTarget> {
+    references.iter()
+        .filter(|x| x.selector.is_none())
+        .filter(move |x| x.condition.as_ref().is_none_or(|condition| scope.get(&condition.selector) == Some(&condition.value)))
+}
+
+/// Converts a column name into the key used for it in JSON output,
+/// i.e. `ClassJobLevel` becomes `classJobLevel`.
+pub(crate) fn json_key(key: &str) -> String {
+    let mut chars = key.chars();
+
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new()
+    }
 }
 
 /// Attempts to convert the value contained in the field to [`u32`].
-fn get_u32(field: &Field) -> Option<u32> {
+pub(crate) fn get_u32(field: &Field) -> Option<u32> {
     match field {
         Field::I8(num) => Some(*num as u32),
         Field::I16(num) => Some(*num as u32),
@@ -221,3 +1010,70 @@ fn get_u32(field: &Field) -> Option<u32> {
         _ => None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::SerializableField;
+
+    const TRICKY_TEXT: &str = "\"Quoted\" Back\\slash\ttab\u{1}\nLine";
+
+    fn tricky_field() -> Field {
+        Field::String(SeString::new(TRICKY_TEXT.as_bytes()))
+    }
+
+    #[test]
+    fn string_field_round_trips() {
+        let output = serde_json::to_vec(&SerializableField(&tricky_field())).unwrap();
+        let parsed: String = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(parsed, TRICKY_TEXT);
+    }
+
+    #[test]
+    fn key_values_round_trip() {
+        let values: KeyValues = [
+            (Cow::Borrowed("Name"), Value::Field(tricky_field())),
+            (Cow::Borrowed("Texts"), Value::Array(vec![Value::Field(tricky_field()), Value::Formatted(TRICKY_TEXT.to_owned())]))
+        ].into_iter().collect();
+
+        for pretty in [false, true] {
+            let mut output = Vec::new();
+            let result = if pretty { values.pretty_write(&mut output) } else { values.write(&mut output) };
+            result.unwrap();
+
+            let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+            assert_eq!(parsed["name"], TRICKY_TEXT);
+            assert_eq!(parsed["texts"][0], TRICKY_TEXT);
+            assert_eq!(parsed["texts"][1], TRICKY_TEXT);
+        }
+    }
+
+    #[test]
+    fn search_match_round_trips() {
+        let matches: SearchMatches = vec![SearchMatch {
+            id: 7,
+            name: Value::Field(tricky_field()),
+            identifier: "Name",
+            field: Some(KeyValue { key: Cow::Borrowed("Description"), value: Value::Field(tricky_field()) })
+        }, SearchMatch {
+            id: 8,
+            name: Value::Formatted(TRICKY_TEXT.to_owned()),
+            identifier: "Name",
+            field: None
+        }];
+
+        let mut output = Vec::new();
+        matches.write(&mut output).unwrap();
+
+        let parsed: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(parsed[0]["id"], 7);
+        assert_eq!(parsed[0]["name"], TRICKY_TEXT);
+        assert_eq!(parsed[0]["column"], "description");
+        assert_eq!(parsed[0]["value"], TRICKY_TEXT);
+        assert_eq!(parsed[1]["column"], "name");
+        assert_eq!(parsed[1]["value"], TRICKY_TEXT);
+    }
+}
@@ -1,23 +1,32 @@
 use ironworks::excel::Field;
 use phf::phf_map;
-use strum::IntoStaticStr;
+use strum::EnumString;
 
-#[derive(Debug, Clone, PartialEq, Eq, IntoStaticStr)]
+#[derive(Debug, Clone, PartialEq, Eq, EnumString)]
 pub enum Sheet {
     Action,
     Status,
-    ContentFinderCondition
+    ContentFinderCondition,
+    Item,
+    /// Any other sheet known to both the game's EXL and EXDSchema, referenced by name.
+    ///
+    /// Sheets without an entry in `sheets.rs` output all of their columns
+    /// and can only be searched by their `Name` column.
+    #[strum(default)]
+    Other(String)
 }
 
-/// A source for a [`SheetLink`], i.e. the type of column in the source
-/// sheet that is used to join to another sheet.
-pub(crate) enum LinkSource {
-    /// The [`SheetLink`] links to another sheet directly by cross-referencing both IDs.
-    ID,
-    /// The [`SheetLink`] links to another sheet by cross-referencing the column defined
-    /// by this variant in the source sheet with the ID of the target sheet.
-    #[allow(dead_code)]
-    Field(&'static str)
+impl Sheet {
+    /// Gets the name of the sheet as it appears in the game's EXL.
+    pub fn name(&self) -> &str {
+        match self {
+            Sheet::Action => "Action",
+            Sheet::Status => "Status",
+            Sheet::ContentFinderCondition => "ContentFinderCondition",
+            Sheet::Item => "Item",
+            Sheet::Other(name) => name
+        }
+    }
 }
 
 /// Represents which columns in the linked sheet are used.
@@ -28,10 +37,12 @@ pub(crate) struct SheetLinkColumn {
     pub target: &'static str
 }
 
-/// Represents a link to another sheet.
+/// Represents a link to another sheet that shares its row IDs with the source sheet.
+///
+/// Columns that reference other sheets by their value don't need a [`SheetLink`];
+/// those references are read from EXDSchema and resolved according to
+/// [`crate::IronworksBuilder::link_depth()`].
 pub(crate) struct SheetLink {
-    /// The link source. Describes how the two sheets should be connected.
-    pub source: LinkSource,
     /// The sheet to link to.
     pub sheet: &'static str,
     /// Which columns in the linked sheet are used, and whether they should be aliased.
@@ -57,7 +68,11 @@ pub(crate) struct SheetData {
     /// Whether data from another sheet should be added to the output.
     pub links: &'static [SheetLink],
     /// Which columns to search in.
-    pub search_columns: &'static [&'static str]
+    pub search_columns: &'static [&'static str],
+    /// Columns that reference another sheet and always get the identifier (usually the name)
+    /// of the referenced row added next to them as `<Column>Name`, even if no link depth is set.
+    /// The name is `null` if the column doesn't reference an existing row.
+    pub resolved_columns: &'static [&'static str]
 }
 
 pub(crate) static SHEET_COLUMNS: phf::Map<&'static str, SheetData> = phf_map! {
@@ -96,12 +111,12 @@ pub(crate) static SHEET_COLUMNS: phf::Map<&'static str, SheetData> = phf_map! {
         ],
         links: &[
             SheetLink {
-                source: LinkSource::ID,
                 sheet: "ActionTransient",
                 columns: &[SheetLinkColumn { source: "Description", target: "Description" }],
                 condition: LinkCondition::Predicate("ClassJob", |x| *x.as_i8().unwrap() != -1)
             }
-        ]
+        ],
+        resolved_columns: &[]
     },
     "Status" => SheetData {
         identifier: "Name",
@@ -122,7 +137,8 @@ pub(crate) static SHEET_COLUMNS: phf::Map<&'static str, SheetData> = phf_map! {
             "Name",
             "Description"
         ],
-        links: &[]
+        links: &[],
+        resolved_columns: &[]
     },
     "ContentFinderCondition" => SheetData {
         identifier: "Name",
@@ -146,6 +162,31 @@ pub(crate) static SHEET_COLUMNS: phf::Map<&'static str, SheetData> = phf_map! {
             "Name",
             "ShortCode"
         ],
-        links: &[]
+        links: &[],
+        resolved_columns: &[]
+    },
+    "Item" => SheetData {
+        identifier: "Name",
+        columns: &[
+            "Name",
+            "Description",
+            "Icon",
+            "LevelItem",
+            "LevelEquip",
+            "ClassJobCategory",
+            "ItemUICategory",
+            "BaseParam",
+            "BaseParamValue",
+            "StackSize"
+        ],
+        search_columns: &[
+            "Name"
+        ],
+        links: &[],
+        resolved_columns: &[
+            "ClassJobCategory",
+            "ItemUICategory",
+            "BaseParam"
+        ]
     }
 };
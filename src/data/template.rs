@@ -0,0 +1,79 @@
+use std::path::Path;
+use minijinja::value::{Value, ValueKind};
+use minijinja::{context, Environment};
+use serde::Serialize;
+use crate::err::Err;
+use super::icons::get_icon_path;
+use super::render::{HtmlWriter, MarkdownWriter, PlainWriter, SeStringWriter};
+
+/// A template that results are rendered through instead of being written as JSON,
+/// e.g. to generate wiki markup or Markdown pages.
+///
+/// Templates use the [minijinja](https://docs.rs/minijinja) syntax (which is largely
+/// compatible with Jinja2). The result is available as `data`, in the same shape as
+/// its JSON output. In addition to minijinja's builtin filters, the following filters are available:
+///
+/// - `icon_path`: the path of an icon ID within the game files, e.g. `ui/icon/000000/000405_hr1.tex`.
+/// - `icon_file`: the file name of an icon ID as written by the `icon` command, e.g. `405.png`.
+/// - `newlines(separator)`: replaces the line breaks of a string (e.g. an SeString's `NewLine` macros)
+///   with the separator, e.g. `{{ data.description | newlines("<br>") }}`.
+/// - `linked(key = "name")`: gets the given key of a linked or resolved row, or the value itself
+///   if it hasn't been resolved (e.g. because it references no row).
+/// - `html`, `markdown` and `plain`: render a string like `--sestring html`, `markdown` or `plain`,
+///   except that colors and emphasis are already lost in the string's text. That is, `html`
+///   escapes the string and `markdown` escapes it for use within a line (or table cell), with
+///   line breaks as `<br>`, and `plain` normalizes its whitespace.
+pub struct Template {
+    environment: Environment<'static>
+}
+
+/// The name that the template is added to its environment by.
+const TEMPLATE_NAME: &str = "template";
+
+impl Template {
+    /// Creates a template from its source. The template is compiled right away,
+    /// so that syntax errors are reported before any data is read.
+    pub fn new(source: String) -> Result<Self, Err> {
+        let mut environment = Environment::new();
+        environment.add_filter("icon_path", get_icon_path);
+        environment.add_filter("icon_file", |id: u32| format!("{}.png", id));
+        environment.add_filter("newlines", |s: String, separator: String| s.replace("\r\n", "\n").replace('\n', &separator));
+        environment.add_filter("linked", linked);
+        environment.add_filter("html", |s: String| render_text(&s, HtmlWriter::default(), HtmlWriter::finish));
+        environment.add_filter("markdown", |s: String| render_text(&s, MarkdownWriter::default(), MarkdownWriter::finish));
+        environment.add_filter("plain", |s: String| render_text(&s, PlainWriter::default(), PlainWriter::finish));
+
+        environment.add_template_owned(TEMPLATE_NAME, source).map_err(Err::TemplateError)?;
+
+        Ok(Self { environment })
+    }
+
+    /// Reads a template from the given file.
+    pub fn from_file(path: &Path) -> Result<Self, Err> {
+        Self::new(std::fs::read_to_string(path).map_err(Err::IoError)?)
+    }
+
+    /// Renders the template with the given result to the [`std::io::Write`] stream.
+    pub fn render(&self, data: &impl Serialize, mut w: impl std::io::Write) -> Result<(), Err> {
+        let template = self.environment.get_template(TEMPLATE_NAME).map_err(Err::TemplateError)?;
+        let output = template.render(context! { data => Value::from_serialize(data) }).map_err(Err::TemplateError)?;
+
+        w.write_all(output.as_bytes()).map_err(Err::IoError)?;
+        w.flush().map_err(Err::IoError)
+    }
+}
+
+/// Writes the text of a string to the writer, as if it were an SeString without macros.
+fn render_text<W: SeStringWriter>(text: &str, mut writer: W, finish: impl FnOnce(W) -> String) -> String {
+    writer.text(text);
+
+    finish(writer)
+}
+
+fn linked(value: Value, key: Option<String>) -> Value {
+    if value.kind() != ValueKind::Map {
+        return value;
+    }
+
+    value.get_attr(key.as_deref().unwrap_or("name")).unwrap_or_default()
+}
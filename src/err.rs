@@ -1,4 +1,5 @@
 use std::{backtrace::Backtrace, borrow::Cow, fmt::Display, io};
+use ironworks::excel::Language;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -6,9 +7,10 @@ pub enum Err {
     GameNotFound,
     VersionNotFound(String),
     SheetNotFound(Cow<'static, str>),
-    RowNotFound(&'static str, u32),
-    ColumnNotFound(&'static str, &'static str),
-    NoIndex(&'static str, &'static str),
+    RowNotFound(Cow<'static, str>, u32),
+    SubrowNotFound(Cow<'static, str>, u32, u16),
+    LanguageNotFound(Cow<'static, str>, Language),
+    ColumnNotFound(Cow<'static, str>, &'static str),
     IconNotFound(String),
     JobNotFound(u32),
     JobAcronymNotFound(String),
@@ -16,7 +18,16 @@ pub enum Err {
     UnsupportedSheet(Cow<'static, str>),
     IoError(io::Error),
     SchemaError(ironworks_schema::Error),
+    DatabaseError(rusqlite::Error),
+    ParquetError(parquet::errors::ParquetError),
+    TemplateError(minijinja::Error),
+    SeStringError(ironworks::sestring::Error),
     IconMissingOut,
+    UnsupportedIconId,
+    OutDirectoryRequired,
+    OutDirectoryUnsupported,
+    OutUnsupported,
+    UnsupportedId,
     NoSubcommand,
     Unknown(u32, Option<Backtrace>)
 }
@@ -28,17 +39,27 @@ impl Display for Err {
             Self::VersionNotFound(s) => writeln!(f, "No schema for game version {} found. You may need to wait for schemas to be updated", s),
             Self::SheetNotFound(s) => writeln!(f, "Sheet {} not found", s),
             Self::RowNotFound(sheet, row) => writeln!(f, "Sheet {} has no row {}", sheet, row),
+            Self::SubrowNotFound(sheet, row, subrow) => writeln!(f, "Sheet {} has no subrow {}:{}", sheet, row, subrow),
+            Self::LanguageNotFound(sheet, language) => writeln!(f, "Sheet {} has no data in language {:?}", sheet, language),
             Self::ColumnNotFound(sheet, column) => writeln!(f, "Sheet {} has no column {}", sheet, column),
-            Self::NoIndex(sheet, column) => writeln!(f, "Column {}::{} cannot be coerced to a u32", sheet, column),
             Self::IconNotFound(path) => writeln!(f, "No icon found at path \"{}\"", path),
             Self::JobNotFound(job) => writeln!(f, "There is no class or job with ID \"{}\"", job),
             Self::JobAcronymNotFound(job) => writeln!(f, "There is no class or job with abbreviation \"{}\"", job),
             Self::UnsupportedIconFormat(format, path) => writeln!(f, "Unsupported icon format {:#04x} at \"{}\"", format, path),
             Self::UnsupportedSheet(sheet) => writeln!(f, "Unsupported sheet type {}", sheet),
             Self::IconMissingOut => writeln!(f, "Icons require an output stream to write the image to"),
+            Self::UnsupportedIconId => writeln!(f, "Icons can only be extracted by their numerical ID, e.g. \"405\" or \"405-410\""),
+            Self::OutDirectoryRequired => writeln!(f, "Multiple results can only be written to a directory. Pass a directory to \"--out\""),
+            Self::OutDirectoryUnsupported => writeln!(f, "This command can't write its result to a directory. Pass a file path to \"--out\""),
+            Self::OutUnsupported => writeln!(f, "This command writes to the path given as its argument and doesn't support \"--out\", \"--format\" or \"--template\""),
+            Self::UnsupportedId => writeln!(f, "This command only accepts a single ID or name"),
             Self::NoSubcommand => writeln!(f, "No subcommand was specified"),
             Self::IoError(err) => err.fmt(f),
             Self::SchemaError(err) => err.fmt(f),
+            Self::DatabaseError(err) => err.fmt(f),
+            Self::ParquetError(err) => err.fmt(f),
+            Self::TemplateError(err) => err.fmt(f),
+            Self::SeStringError(err) => err.fmt(f),
             Self::Unknown(code, trace) => if let Some(trace) = trace {
                 writeln!(f, "An unknown error (error code: {}) occurred at:\n{}", code, trace)
             } else {
@@ -1,14 +1,18 @@
-use std::io::stdout;
+use std::io::{stdout, BufWriter};
+use std::ops::Deref;
+use std::path::Path;
 use std::process::ExitCode;
 use ironworks_cli::err::Err;
 
 use clap::{crate_name, crate_version, Parser};
-use cli::{Cli, Command, IconArgs, JobActionsCommandArgs, RoleActionsCommandArgs, SheetCommandArgs};
+use serde::Serialize;
+use cli::{Cli, Command, DatabaseArgs, ExportArgs, GenericSheetCommandArgs, IconArgs, JobActionsCommandArgs, JobsArgs, ParquetArgs, RoleActionsCommandArgs, SheetCommandArgs};
 use ironworks_cli::{self, Id};
-use ironworks_cli::err::ToUnknownErr;
-use ironworks_cli::{IronworksBuilder, IronworksCli, Sheet, WritableResult};
+use ironworks_cli::{DEFAULT_DATABASE_SHEETS, IronworksBuilder, IronworksCli, OutputFormat, RowValues, SeStringFormat, Sheet, SheetRow, SheetRows, SheetSchema, TabularResult, Template, WithSchema, WritableResult};
+use output::{write_file, Output};
 
 mod cli;
+mod output;
 
 fn main() -> ExitCode {
     let cli = Cli::parse();
@@ -28,13 +32,51 @@ fn process(cli: Cli) -> Result<(), Err> {
         return Ok(());
     }
 
+    // The template is only read once, even if it is rendered for many rows.
+    let template = cli.template.as_deref().map(Template::from_file).transpose()?;
+    let template = template.as_ref();
+
     match cli.command.as_ref().ok_or(Err::NoSubcommand)? {
-        Command::Icon(IconArgs { id }) => ironworks_cli::extract_icon(*id, cli.game.as_deref(), stdout()),
-        Command::JobActions(JobActionsCommandArgs { base, names }) => print(ironworks(&cli)?.get_job_actions(base.id.clone())?.writable(*names), base.pretty),
-        Command::RoleActions(RoleActionsCommandArgs { role, names, pretty }) => print(ironworks(&cli)?.get_role_actions(*role)?.writable(*names), *pretty),
-        Command::ContentFinderCondition(SheetCommandArgs { id, pretty }) => process_sheet_command(Sheet::ContentFinderCondition, id, &cli, *pretty),
-        Command::Action(SheetCommandArgs { id, pretty }) => process_sheet_command(Sheet::Action, id, &cli, *pretty),
-        Command::Status(SheetCommandArgs { id, pretty }) => process_sheet_command(Sheet::Status, id, &cli, *pretty)
+        Command::Icon(IconArgs { id }) => process_icon_command(id, &cli),
+        Command::JobActions(JobActionsCommandArgs { base, names }) => print(ironworks(&cli)?.get_job_actions(base.id.clone())?.writable(*names), &cli, template, base.pretty),
+        Command::Jobs(JobsArgs { id: Some(id), pretty }) => print(ironworks(&cli)?.get_job(id.clone())?, &cli, template, *pretty),
+        Command::Jobs(JobsArgs { id: None, pretty }) => print_each(ironworks(&cli)?.get_jobs()?, |x| x.id, &cli, template, *pretty),
+        Command::RoleActions(RoleActionsCommandArgs { role, names, pretty }) => print(ironworks(&cli)?.get_role_actions(*role)?.writable(*names), &cli, template, *pretty),
+        Command::ContentFinderCondition(SheetCommandArgs { id, pretty }) => process_sheet_command(Sheet::ContentFinderCondition, id, &cli, template, *pretty),
+        Command::Action(SheetCommandArgs { id, pretty }) => process_sheet_command(Sheet::Action, id, &cli, template, *pretty),
+        Command::Status(SheetCommandArgs { id, pretty }) => process_sheet_command(Sheet::Status, id, &cli, template, *pretty),
+        Command::Item(SheetCommandArgs { id, pretty }) => process_sheet_command(Sheet::Item, id, &cli, template, *pretty),
+        Command::Sheet(GenericSheetCommandArgs { sheet, base }) => process_sheet_command(sheet.clone(), &base.id, &cli, template, base.pretty),
+        Command::Database(_) | Command::Parquet(_) if cli.out.is_some() || cli.format != OutputFormat::Json || template.is_some() => Err(Err::OutUnsupported),
+        Command::Database(DatabaseArgs { path, sheets }) => {
+            let sheets = if sheets.is_empty() { DEFAULT_DATABASE_SHEETS.to_vec() } else { sheets.clone() };
+
+            ironworks(&cli)?.write_database(&sheets, path)
+        },
+        Command::Parquet(ParquetArgs { sheet, path }) => {
+            let ironworks = ironworks(&cli)?;
+
+            write_file(path, |w| ironworks.write_parquet(sheet, w))
+        },
+        Command::Export(ExportArgs { sheet, pretty }) => {
+            let ironworks = ironworks(&cli)?;
+            let mut rows = ironworks.export(sheet)?;
+            let schema = if cli.schema { Some(ironworks.sheet_schema(sheet)?) } else { None };
+            let output = Output::of(&cli);
+
+            if let Output::Directory(directory) = &output {
+                return write_row_files(rows, directory, schema, &cli, template, *pretty);
+            }
+
+            if let Some(schema) = schema {
+                rows = rows.with_schema(schema);
+            }
+
+            match output {
+                Output::File(path) => write_file(&path, |w| write_sheet_rows(rows, &cli, template, *pretty, w)),
+                _ => write_sheet_rows(rows, &cli, template, *pretty, BufWriter::new(stdout().lock()))
+            }
+        }
     }
 }
 
@@ -55,23 +97,189 @@ fn ironworks(cli: &Cli) -> Result<IronworksCli, Err> {
         builder = builder.force_refresh()
     }
 
+    if cli.depth > 0 {
+        builder = builder.link_depth(cli.depth);
+    }
+
+    if let Some(language) = cli.language {
+        builder = builder.language(language);
+    }
+
+    if cli.all_languages {
+        builder = builder.all_languages();
+    }
+
+    if cli.sort_keys {
+        builder = builder.sort_keys();
+    }
+
+    builder = builder.sestring_format(sestring_format(cli));
+
+    if let Some(context) = &cli.context {
+        builder = builder.player_context(context.clone());
+    }
+
     builder.build()
 }
 
-fn print(input: impl WritableResult, pretty: bool) -> Result<(), Err> {
-    if pretty {
-        input.pretty_write(stdout()).to_unknown_err(29)
+/// Gets the format that strings are rendered in. Markdown output renders
+/// strings as Markdown, unless another string format was requested.
+fn sestring_format(cli: &Cli) -> SeStringFormat {
+    if cli.format == OutputFormat::Markdown && cli.sestring == SeStringFormat::Text {
+        SeStringFormat::Markdown
     } else {
-        input.write(stdout()).to_unknown_err(30)
+        cli.sestring
+    }
+}
+
+fn print(input: impl WritableResult + TabularResult + Serialize, cli: &Cli, template: Option<&Template>, pretty: bool) -> Result<(), Err> {
+    match Output::of(cli) {
+        Output::Stdout => write_result(&input, cli, template, pretty, stdout().lock()),
+        Output::File(path) => write_file(&path, |w| write_result(&input, cli, template, pretty, w)),
+        Output::Directory(_) => Err(Err::OutDirectoryUnsupported)
+    }
+}
+
+/// Prints a result that consists of multiple entities. If `--out` is a directory,
+/// each entity is written to its own file named after its ID instead, e.g. `<id>.json`.
+fn print_each<R, T>(input: R, id: impl Fn(&T) -> u32, cli: &Cli, template: Option<&Template>, pretty: bool) -> Result<(), Err>
+where
+    R: WritableResult + TabularResult + Serialize + Deref<Target = [T]>,
+    T: WritableResult + TabularResult + Serialize
+{
+    let Output::Directory(directory) = Output::of(cli) else {
+        return print(input, cli, template, pretty);
+    };
+
+    for element in input.iter() {
+        let file_name = format!("{}.{}", id(element), file_extension(cli, template));
+        write_file(&directory.join(file_name), |w| write_result(element, cli, template, pretty, w))?;
     }
+
+    Ok(())
+}
+
+/// Gets the extension of files that are written to an `--out` directory.
+fn file_extension(cli: &Cli, template: Option<&Template>) -> &'static str {
+    if template.is_some() { "txt" } else { cli.format.extension() }
 }
 
-fn process_sheet_command(sheet: Sheet, id: &Id, cli: &Cli, pretty: bool) -> Result<(), Err> {
+fn write_result(input: &(impl WritableResult + TabularResult + Serialize), cli: &Cli, template: Option<&Template>, pretty: bool, w: impl std::io::Write) -> Result<(), Err> {
+    let format = cli.format;
+
+    if let Some(template) = template {
+        template.render(input, w)
+    } else if let Some(delimiter) = format.delimiter() {
+        input.table().write(w, delimiter).map_err(Err::IoError)
+    } else if format == OutputFormat::Ndjson {
+        input.write_lines(w).map_err(Err::IoError)
+    } else if format == OutputFormat::Text {
+        input.write_text(w).map_err(Err::IoError)
+    } else if format == OutputFormat::Markdown {
+        input.write_markdown(w, sestring_format(cli) != SeStringFormat::Markdown).map_err(Err::IoError)
+    } else if pretty {
+        input.pretty_write(w).map_err(Err::IoError)
+    } else {
+        input.write(w).map_err(Err::IoError)
+    }
+}
+
+fn write_sheet_rows(rows: SheetRows, cli: &Cli, template: Option<&Template>, pretty: bool, w: impl std::io::Write) -> Result<(), Err> {
+    let format = cli.format;
+
+    if let Some(template) = template {
+        // Templates may access any row, so all rows have to be read first.
+        template.render(&rows.collect::<Result<Vec<_>, Err>>()?, w)
+    } else if let Some(delimiter) = format.delimiter() {
+        rows.write_table(w, delimiter)
+    } else if format == OutputFormat::Ndjson {
+        rows.write_lines(w)
+    } else if format == OutputFormat::Markdown {
+        rows.write_markdown(w, sestring_format(cli) != SeStringFormat::Markdown)
+    } else {
+        rows.write_all(w, pretty)
+    }
+}
+
+/// Writes each row to its own file in the directory, e.g. `<id>.json` (or `<id>.<subrow>.json`
+/// for subrows). If a schema is given, it is written to `schema.json`.
+fn write_row_files<'a>(rows: impl IntoIterator<Item = Result<SheetRow<'a>, Err>>, directory: &Path, schema: Option<SheetSchema>, cli: &Cli, template: Option<&Template>, pretty: bool) -> Result<(), Err> {
+    let extension = file_extension(cli, template);
+
+    if let Some(schema) = schema {
+        write_file(&directory.join("schema.json"), |w| serde_json::to_writer_pretty(w, &schema).map_err(|x| Err::IoError(x.into())))?;
+    }
+
+    for row in rows {
+        let row = row?;
+        let file_name = match row.subrow_id {
+            Some(subrow_id) => format!("{}.{}.{}", row.id, subrow_id, extension),
+            None => format!("{}.{}", row.id, extension)
+        };
+
+        write_file(&directory.join(file_name), |w| write_result(&row, cli, template, pretty, w))?;
+    }
+
+    Ok(())
+}
+
+fn process_icon_command(id: &Id, cli: &Cli) -> Result<(), Err> {
+    let output = Output::of(cli);
+
+    if output.is_terminal() {
+        return Err(Err::IconMissingOut);
+    }
+
+    match (id, output) {
+        (Id::Index(id), Output::Stdout) => ironworks_cli::extract_icon(*id, cli.game.as_deref(), stdout().lock()),
+        (Id::Index(id), Output::File(path)) => write_file(&path, |w| ironworks_cli::extract_icon(*id, cli.game.as_deref(), w)),
+        (Id::Index(id), Output::Directory(directory)) => write_icon_files(&[*id], &directory, cli),
+        (Id::Indices(ids), Output::Directory(directory)) => write_icon_files(ids, &directory, cli),
+        (Id::Indices(_), _) => Err(Err::OutDirectoryRequired),
+        (Id::Name(_) | Id::Subrow(_, _), _) => Err(Err::UnsupportedIconId)
+    }
+}
+
+fn write_icon_files(ids: &[u32], directory: &Path, cli: &Cli) -> Result<(), Err> {
+    for id in ids {
+        write_file(&directory.join(format!("{}.png", id)), |w| ironworks_cli::extract_icon(*id, cli.game.as_deref(), w))?;
+    }
+
+    Ok(())
+}
+
+fn process_sheet_command(sheet: Sheet, id: &Id, cli: &Cli, template: Option<&Template>, pretty: bool) -> Result<(), Err> {
     let ironworks = ironworks(cli)?;
 
+    if let Output::Directory(directory) = Output::of(cli) {
+        let rows = match id {
+            Id::Name(name) => return print_each(ironworks.search(sheet, name)?, |x| x.id, cli, template, pretty),
+            Id::Index(index) => match ironworks.get(sheet.clone(), *index)? {
+                RowValues::Row(values) => vec![SheetRow { id: *index, subrow_id: None, values }],
+                RowValues::Subrows(rows) => rows
+            },
+            Id::Indices(indices) => ironworks.get_rows(sheet.clone(), indices)?,
+            Id::Subrow(index, subrow) => vec![SheetRow { id: *index, subrow_id: Some(*subrow), values: ironworks.get_subrow(sheet.clone(), *index, *subrow)? }]
+        };
+        let schema = if cli.schema { Some(ironworks.sheet_schema(&sheet)?) } else { None };
+
+        return write_row_files(rows.into_iter().map(Ok), &directory, schema, cli, template, pretty);
+    }
+
     match id {
-        Id::Name(name) => print(ironworks.search(sheet, name)?, pretty),
-        Id::Index(index) => print(ironworks.get(sheet, *index)?, pretty),
+        Id::Name(name) => print_each(ironworks.search(sheet, name)?, |x| x.id, cli, template, pretty),
+        Id::Index(index) => print_rows(ironworks.get(sheet.clone(), *index)?, &sheet, &ironworks, cli, template, pretty),
+        Id::Indices(indices) => print_rows(ironworks.get_rows(sheet.clone(), indices)?, &sheet, &ironworks, cli, template, pretty),
+        Id::Subrow(index, subrow) => print_rows(ironworks.get_subrow(sheet.clone(), *index, *subrow)?, &sheet, &ironworks, cli, template, pretty),
+    }
+}
+
+/// Prints rows of the sheet, including the sheet's schema if requested.
+fn print_rows(rows: impl WritableResult + TabularResult + Serialize, sheet: &Sheet, ironworks: &IronworksCli, cli: &Cli, template: Option<&Template>, pretty: bool) -> Result<(), Err> {
+    if cli.schema {
+        print(WithSchema { schema: ironworks.sheet_schema(sheet)?, data: rows }, cli, template, pretty)
+    } else {
+        print(rows, cli, template, pretty)
     }
 }
 
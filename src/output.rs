@@ -0,0 +1,92 @@
+use std::fs::{self, File};
+use std::io::{self, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use ironworks_cli::err::Err;
+use crate::cli::Cli;
+
+/// Where the results of a command are written to, as specified by `--out`.
+pub(crate) enum Output {
+    Stdout,
+    /// A single file that all results are written to.
+    File(PathBuf),
+    /// A directory that each result is written to as a separate file.
+    Directory(PathBuf)
+}
+
+impl Output {
+    pub fn of(cli: &Cli) -> Self {
+        match &cli.out {
+            None => Output::Stdout,
+            Some(path) if path.is_dir() || path.to_string_lossy().ends_with(['/', '\\']) => Output::Directory(path.to_path_buf()),
+            Some(path) => Output::File(path.to_path_buf())
+        }
+    }
+
+    /// Whether binary data (i.e. icons) would be written to a terminal.
+    pub fn is_terminal(&self) -> bool {
+        matches!(self, Output::Stdout) && io::stdout().is_terminal()
+    }
+}
+
+/// Writes to the given file via `f`. The file only appears once `f` succeeded,
+/// so that no partially written files are left behind on failure.
+pub(crate) fn write_file(path: &Path, f: impl FnOnce(&mut AtomicFile) -> Result<(), Err>) -> Result<(), Err> {
+    let mut file = AtomicFile::create(path).map_err(Err::IoError)?;
+    f(&mut file)?;
+
+    file.commit().map_err(Err::IoError)
+}
+
+/// A file that is written to a temporary file next to its destination first,
+/// and only moved to its destination once it is committed.
+pub(crate) struct AtomicFile {
+    path: PathBuf,
+    temp_path: PathBuf,
+    file: Option<BufWriter<File>>
+}
+
+impl AtomicFile {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|x| !x.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+
+        let file_name = path.file_name().ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "output path has no file name"))?;
+        let temp_path = path.with_file_name(format!(".{}.{}.tmp", file_name.to_string_lossy(), process::id()));
+        let file = BufWriter::new(File::create(&temp_path)?);
+
+        Ok(Self { path: path.to_path_buf(), temp_path, file: Some(file) })
+    }
+
+    /// Flushes the file and moves it to its destination, replacing any existing file.
+    ///
+    /// The file isn't synced to disk, since that would slow down writing thousands of files
+    /// (e.g. with `export --out dir/`). The rename alone ensures that no partial files appear.
+    pub fn commit(mut self) -> io::Result<()> {
+        if let Some(file) = self.file.take() {
+            file.into_inner().map_err(|x| x.into_error())?;
+        }
+
+        fs::rename(&self.temp_path, &self.path)
+    }
+}
+
+impl Write for AtomicFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.as_mut().map_or(Ok(0), |x| x.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.as_mut().map_or(Ok(()), |x| x.flush())
+    }
+}
+
+impl Drop for AtomicFile {
+    fn drop(&mut self) {
+        // If the file hasn't been committed, the temporary file is discarded.
+        // Otherwise it has already been moved, so there's nothing to remove.
+        self.file.take();
+        let _ = fs::remove_file(&self.temp_path);
+    }
+}